@@ -0,0 +1,265 @@
+//! Off-chain helpers for building vault instructions
+//!
+//! ```
+//! use solana_keypair::Keypair;
+//! use solana_message::Message;
+//! use solana_pubkey::Pubkey;
+//! use solana_signer::Signer;
+//! use solana_transaction::Transaction;
+//! use vault::client::{build_initialize_ix, find_vault_pda};
+//! use vault::state::DEFAULT_VAULT_LABEL;
+//!
+//! let program_id = Pubkey::new_unique();
+//! let payer = Keypair::new();
+//! let (vault, bump) = find_vault_pda(&payer.pubkey(), &DEFAULT_VAULT_LABEL, &program_id);
+//!
+//! let ix = build_initialize_ix(&program_id, &payer.pubkey(), &vault, bump);
+//! let message = Message::new(&[ix], Some(&payer.pubkey()));
+//! let tx = Transaction::new(&[&payer], message, solana_hash::Hash::default());
+//! assert!(tx.is_signed());
+//! ```
+
+use pinocchio::{error::ProgramError, Address};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::state::vault::{find_vault_address, Vault, VaultData, VAULT_LABEL_LEN};
+use crate::state::vault_counter::find_vault_counter_address;
+
+/// System program id, the all-zero key
+pub const SYSTEM_PROGRAM_ID: Pubkey = Pubkey::new_from_array([0; 32]);
+
+pub use crate::instructions::discriminator;
+
+/// Convert an on-chain `Address` into a client `Pubkey`
+///
+/// Goes through the raw 32 bytes, so it keeps working if the two types stop
+/// being the same type in some future version pairing.
+pub fn to_pubkey(addr: &Address) -> Pubkey {
+    Pubkey::new_from_array(addr.to_bytes())
+}
+
+/// Convert a client `Pubkey` into an on-chain `Address`
+pub fn to_address(pk: &Pubkey) -> Address {
+    Address::new_from_array(pk.to_bytes())
+}
+
+/// Derive the vault PDA and its canonical bump for `owner` and `label`
+pub fn find_vault_pda(
+    owner: &Pubkey,
+    label: &[u8; VAULT_LABEL_LEN],
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    let (address, bump) = find_vault_address(&to_address(owner), label, &to_address(program_id));
+    (to_pubkey(&address), bump)
+}
+
+/// Derive the vault counter PDA of `owner`, which the creating instructions
+/// and `CloseVault` take
+pub fn find_vault_counter_pda(owner: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    let (address, _) = find_vault_counter_address(&to_address(owner), &to_address(program_id));
+    to_pubkey(&address)
+}
+
+/// Owner, vault and system program, the accounts shared by the core
+/// instructions
+fn core_accounts(signer: &Pubkey, vault: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*signer, true),
+        AccountMeta::new(*vault, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ]
+}
+
+/// Build the Initialize instruction for `payer`'s default-label vault
+/// Data layout: [0x00, bump]
+pub fn build_initialize_ix(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    vault: &Pubkey,
+    bump: u8,
+) -> Instruction {
+    let mut accounts = core_accounts(payer, vault);
+    accounts.push(AccountMeta::new(
+        find_vault_counter_pda(payer, program_id),
+        false,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: vec![discriminator::INITIALIZE, bump],
+    }
+}
+
+/// Build the Deposit instruction
+/// Data layout: [0x01, amount_le_bytes(8)]
+pub fn build_deposit_ix(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    vault: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut data = vec![discriminator::DEPOSIT];
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: *program_id,
+        accounts: core_accounts(owner, vault),
+        data,
+    }
+}
+
+/// Build the Withdraw instruction
+/// Data layout: [0x02, amount_le_bytes(8), bump]
+pub fn build_withdraw_ix(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    vault: &Pubkey,
+    amount: u64,
+    bump: u8,
+) -> Instruction {
+    let mut data = vec![discriminator::WITHDRAW];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(bump);
+    Instruction {
+        program_id: *program_id,
+        accounts: core_accounts(owner, vault),
+        data,
+    }
+}
+
+/// Vault account data decoded by [`decode_vault`], in whichever layout it
+/// was written
+pub enum VersionedVault<'a> {
+    /// The original header-only account: discriminator, owner and amount,
+    /// exactly [`VaultData::LEN`] bytes with no version field
+    V1(VaultData),
+    /// The extended layout, which records the `PROGRAM_VERSION` of the build
+    /// that created it
+    V2 {
+        header: VaultData,
+        created_by_version: u32,
+        vault: Vault<'a>,
+    },
+}
+
+impl VersionedVault<'_> {
+    /// The header fields every layout shares
+    pub fn header(&self) -> &VaultData {
+        match self {
+            Self::V1(header) | Self::V2 { header, .. } => header,
+        }
+    }
+
+    /// The creating program version, `None` for v1 accounts, which predate
+    /// the field
+    pub fn created_by_version(&self) -> Option<u32> {
+        match self {
+            Self::V1(_) => None,
+            Self::V2 {
+                created_by_version, ..
+            } => Some(*created_by_version),
+        }
+    }
+}
+
+/// Decode vault account data of any layout version through one entry point
+///
+/// A v1 account is only the header, so its length identifies it; anything
+/// at least [`Vault::LEN`] long is decoded as v2 and tagged with its stored
+/// creating version. Lengths in between match neither layout and fail with
+/// `InvalidAccountData`, and a wrong discriminator fails as usual.
+pub fn decode_vault(data: &[u8]) -> Result<VersionedVault<'_>, ProgramError> {
+    let header = VaultData::unpack(data)?;
+    if data.len() == VaultData::LEN {
+        return Ok(VersionedVault::V1(header));
+    }
+    if data.len() < Vault::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let vault = Vault::from_slice(data)?;
+    Ok(VersionedVault::V2 {
+        header,
+        created_by_version: vault.created_by_version(),
+        vault,
+    })
+}
+
+/// Prepend a compute unit limit of `units` to `ix`
+///
+/// Wraps any of the builders above for instructions that can outgrow the
+/// default limit, such as `BatchDeposit` with many entries or cosigned
+/// withdrawals; the returned instructions go into one transaction in order.
+pub fn with_compute_budget(units: u32, ix: Instruction) -> Vec<Instruction> {
+    vec![ComputeBudgetInstruction::set_compute_unit_limit(units), ix]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::vault::{VaultMut, PROGRAM_VERSION, VAULT_DISCRIMINATOR};
+
+    fn header() -> VaultData {
+        VaultData {
+            discriminator: VAULT_DISCRIMINATOR,
+            owner: [7; 32],
+            amount: 1_000_000_000,
+        }
+    }
+
+    #[test]
+    fn decode_vault_reads_v1_and_v2_through_one_entry_point() {
+        let mut v1 = [0u8; VaultData::LEN];
+        header().pack_into(&mut v1);
+
+        let mut v2 = [0u8; Vault::LEN];
+        let mut vault = VaultMut::from_bytes(&mut v2);
+        vault.set_header(&header());
+        vault.set_created_by_version(PROGRAM_VERSION);
+        vault.set_label(b"trading\0\0\0\0\0\0\0\0\0");
+
+        let old = decode_vault(&v1).unwrap();
+        let new = decode_vault(&v2).unwrap();
+        assert_eq!(old.header(), new.header());
+        assert_eq!(*new.header(), header());
+        assert!(matches!(old, VersionedVault::V1(_)));
+        assert_eq!(old.created_by_version(), None);
+        assert_eq!(new.created_by_version(), Some(PROGRAM_VERSION));
+        let VersionedVault::V2 { vault, .. } = new else {
+            panic!("extended buffer should decode as v2");
+        };
+        assert_eq!(vault.label(), b"trading\0\0\0\0\0\0\0\0\0");
+
+        // A length between the two layouts, or a wrong discriminator, is
+        // neither version
+        assert_eq!(
+            decode_vault(&v2[..VaultData::LEN + 1]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+        v1[0] ^= 1;
+        assert!(decode_vault(&v1).is_err());
+    }
+
+    #[test]
+    fn with_compute_budget_puts_the_limit_first() {
+        let program_id = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let ix = build_deposit_ix(&program_id, &owner, &vault, 500);
+
+        let ixs = with_compute_budget(400_000, ix.clone());
+
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(
+            ixs[0].program_id,
+            Pubkey::from_str_const("ComputeBudget111111111111111111111111111111")
+        );
+        assert!(ixs[0].accounts.is_empty());
+        // `SetComputeUnitLimit` is tag 2 followed by the units (u32 LE)
+        let mut limit = vec![2];
+        limit.extend_from_slice(&400_000u32.to_le_bytes());
+        assert_eq!(ixs[0].data, limit);
+        assert_eq!(ixs[1], ix);
+    }
+}
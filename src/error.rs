@@ -0,0 +1,73 @@
+use pinocchio::error::ProgramError;
+
+/// Vault program errors
+///
+/// Each variant is returned to clients as `ProgramError::Custom(code)`,
+/// where `code` is the variant's discriminant. Codes are stable: new
+/// variants are only ever appended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum VaultError {
+    /// A deposit would leave the payer below its own rent-exempt minimum
+    PayerWouldLoseRentExemption = 0,
+    /// The vault's lamports did not increase by exactly the transferred amount
+    TransferMismatch = 1,
+    /// A CPI withdraw came from a program not on the vault's allowed list
+    UnauthorizedCaller = 2,
+    /// A deposit arrived before the vault's deposit delay had elapsed
+    VaultTooNew = 3,
+    /// A second withdraw-type instruction hit a single-op-per-transaction vault
+    DuplicateVaultOperation = 4,
+    /// A close found the tracked amount out of step with the backing lamports
+    /// and was not forced
+    TrackedAmountMismatch = 5,
+    /// A withdraw arrived before the vault's unlock slot, or tried to bring
+    /// an active unlock slot forward
+    VaultLocked = 6,
+    /// The signer is not the vault's stored owner
+    OwnerMismatch = 7,
+    /// The vault's tracked amount or backing lamports cannot cover a withdraw
+    InsufficientBalance = 8,
+    /// The account is not a vault: wrong data length or discriminator
+    InvalidDiscriminator = 9,
+    /// A required signer did not sign the transaction
+    NotSigner = 10,
+    /// The owner slot holds the system program or another executable account
+    InvalidOwnerAccount = 11,
+    /// A deposit would take the tracked amount above the vault's maximum
+    /// balance
+    MaxBalanceExceeded = 12,
+    /// A deposit into a whole-unit vault was not a whole number of SOL
+    NonIntegralAmount = 13,
+    /// The signer is not the program's upgrade authority
+    NotUpgradeAuthority = 14,
+    /// The vault is paused, blocking deposits and withdrawals
+    Paused = 15,
+    /// An escrow vault withdraw was not co-signed by the counterparty, or a
+    /// withdrawal above the single signature limit by the cosigner
+    MissingCosigner = 16,
+    /// The signer is not the vault's authority
+    AuthorityMismatch = 17,
+    /// A deposit was smaller than the vault's minimum deposit
+    BelowMinDeposit = 18,
+    /// The treasury passed with a fee-charging deposit or initialize is not
+    /// the config's
+    TreasuryMismatch = 19,
+    /// A refund's recipient has no receipt for the vault, or is its owner
+    DepositorMismatch = 20,
+    /// A tombstone's grace period ended before `Reopen`
+    GracePeriodExpired = 21,
+    /// A withdraw from a no-flash vault follows a deposit into it in the same
+    /// transaction
+    SameTransactionDeposit = 22,
+    /// The signer is not the global config's admin
+    AdminMismatch = 23,
+    /// The owner already has `MAX_VAULTS_PER_OWNER` vaults
+    VaultLimitReached = 24,
+}
+
+impl From<VaultError> for ProgramError {
+    fn from(e: VaultError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
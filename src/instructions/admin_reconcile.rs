@@ -0,0 +1,75 @@
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+use solana_program_log::log;
+
+use crate::error::VaultError;
+use crate::instructions::withdraw;
+use crate::state::config::Config;
+use crate::state::vault::{Vault, VaultAmount, VaultMut};
+use crate::utils::{events, helpers::vault_backing};
+
+/// Process admin-reconcile instruction
+///
+/// Sets any vault's tracked amount to its backing lamports (balance minus
+/// rent-exempt minimum), for repairing desynced vaults during incident
+/// recovery. Unlike `SweepDust` it can lower the amount as well as raise
+/// it, and it is signed by the global config's admin instead of the owner.
+/// Does nothing if the vault is already in sync.
+///
+/// Accounts:
+/// 0. `[signer]` admin named by the global config
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` config PDA account
+pub fn handler(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [admin, vault, config, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    validate_admin(program_id, admin, config)?;
+
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    // SAFETY: no active borrows of vault data at this point
+    let tracked = unsafe { Vault::from_account(vault) }?.amount();
+    withdraw::verify_stored_address(program_id, vault)?;
+
+    let backing = vault_backing(vault, &Rent::get()?)?;
+    if tracked == backing as VaultAmount {
+        return Ok(());
+    }
+
+    // SAFETY: the read view above is no longer used
+    unsafe { VaultMut::from_account_unchecked(vault).set_amount(backing as VaultAmount) };
+    log!(
+        "VAULT_ADMIN_RECONCILE tracked={} backing={}",
+        tracked,
+        backing
+    );
+
+    events::record(vault, "admin_reconcile")?;
+
+    Ok(())
+}
+
+/// Validate that `admin` signed and is the admin named by `config`
+pub(crate) fn validate_admin(
+    program_id: &Address,
+    admin: &AccountView,
+    config: &AccountView,
+) -> ProgramResult {
+    if !admin.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    // Only `InitializeConfig` writes a config, and only at the config PDA
+    if !config.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    // SAFETY: no active borrows of config data at this point
+    if unsafe { Config::from_account(config) }?.admin() != admin.address() {
+        return Err(VaultError::AdminMismatch.into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::error::VaultError;
+use crate::instructions::initialize;
+use crate::state::vault::DEFAULT_VAULT_LABEL;
+use crate::utils::helpers::{owner_account_check, system_program_check, uninitialized_check};
+
+/// Process allocate-vault instruction
+///
+/// First phase of two-phase creation: reserves the vault PDA by creating it
+/// with the vault size and program ownership, but leaves the data zeroed so
+/// the account is not yet a vault. `InitializeAllocated` completes it. The
+/// reserved account already counts towards the owner's vault limit.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner / payer
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program
+/// 3. `[writable]` owner's vault counter PDA `[b"vault_counter", owner]`
+pub fn handler(program_id: &Address, accounts: &[AccountView], bump: u8) -> ProgramResult {
+    let [payer, vault, system_program, counter] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    owner_account_check(payer)?;
+    if !payer.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    uninitialized_check(vault)?;
+
+    initialize::verify_bump(program_id, payer, vault, &DEFAULT_VAULT_LABEL, bump)?;
+
+    initialize::count_vault(program_id, payer, counter)?;
+    initialize::allocate(program_id, payer, vault, &DEFAULT_VAULT_LABEL, bump)
+}
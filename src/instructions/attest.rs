@@ -0,0 +1,45 @@
+use pinocchio::cpi::set_return_data;
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, AMOUNT_LEN};
+
+/// Size of the `Attest` return data
+pub const ATTEST_RETURN_LEN: usize = 32 + 32 + AMOUNT_LEN + 8 + 8;
+
+/// Process attest instruction
+///
+/// Writes an owner-signed, point-in-time snapshot of the vault as return
+/// data. The owner's signature on the transaction and the runtime's record
+/// of which program set the return data let a relayer carry the snapshot
+/// to systems that trust this program. Read only.
+///
+/// Return data: [vault: [u8; 32], owner: [u8; 32], amount: VaultAmount,
+/// slot: u64, seq: u64]
+///
+/// Accounts:
+/// 0. `[signer]` owner
+/// 1. `[]` vault PDA account
+pub fn handler(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [owner, vault, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate(program_id, owner, vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    let amount_end = 64 + AMOUNT_LEN;
+
+    let mut data = [0u8; ATTEST_RETURN_LEN];
+    data[0..32].copy_from_slice(vault.address().as_ref());
+    data[32..64].copy_from_slice(&vault_state.owner_bytes());
+    data[64..amount_end].copy_from_slice(&vault_state.amount().to_le_bytes());
+    data[amount_end..amount_end + 8].copy_from_slice(&Clock::get()?.slot.to_le_bytes());
+    data[amount_end + 8..].copy_from_slice(&vault_state.seq().to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
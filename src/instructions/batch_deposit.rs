@@ -0,0 +1,48 @@
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::error::VaultError;
+use crate::instructions::deposit;
+use crate::utils::helpers::{owner_account_check, system_program_check};
+
+/// Most vaults one `BatchDeposit` can fund
+///
+/// Keeps the transaction well under the runtime's account and compute
+/// limits, since every entry is a transfer CPI.
+pub const MAX_BATCH_DEPOSITS: usize = 8;
+
+/// Process batch-deposit instruction
+///
+/// Funds several vaults from one signer atomically, as a `Contribute` to
+/// each: amount `i` goes to vault account `i`, and any failing entry fails
+/// the whole batch. There is no room for fee accounts, so a vault that
+/// charges a deposit fee fails the batch.
+///
+/// Accounts:
+/// 0. `[signer, writable]` funder
+/// 1. `[]` system_program
+/// 2. `[writable]` vault PDA accounts, one per amount, in amount order
+pub fn handler(program_id: &Address, accounts: &[AccountView], amounts: &[u64]) -> ProgramResult {
+    let [funder, system_program, vaults @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    // Every amount needs exactly one vault
+    if vaults.len() != amounts.len() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Validate funder is a signable account and signed
+    owner_account_check(funder)?;
+    if !funder.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    for (vault, &amount) in vaults.iter().zip(amounts) {
+        deposit::validate_vault(program_id, vault)?;
+        deposit::credit(program_id, funder, vault, amount, 0, None, &[])?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+use solana_program_log::log;
+
+use crate::error::VaultError;
+use crate::instructions::{initialize, withdraw};
+use crate::state::tombstone::Tombstone;
+use crate::state::vault::{Vault, VaultAmount, VaultMut};
+use crate::utils::events;
+use crate::utils::helpers::vault_backing;
+
+/// Process close-vault instruction
+///
+/// Sends every lamport in the vault, tracked amount and rent alike, to the
+/// owner, zeroes the data and tombstones the discriminator so the account
+/// can never be read as a vault again, even within the same transaction.
+///
+/// If the tracked amount disagrees with the lamports backing it, the close
+/// fails with `TrackedAmountMismatch` unless `force` is set, in which case
+/// the discrepancy is logged and the close proceeds.
+///
+/// The whole buffer is zeroed, not just the discriminator: the runtime only
+/// garbage collects the emptied account after the transaction, so a later
+/// instruction that sends lamports back revives it with whatever data it
+/// still holds. A revived account carries no owner, amount or settings, and
+/// `Initialize` cannot reuse it since it is no longer system-owned.
+///
+/// A nonzero `grace_slots` keeps a small tombstone instead of removing the
+/// account: it records the owner and the close slot and holds just its own
+/// rent, so `Reopen` can restore the vault within that many slots.
+///
+/// Either way the vault leaves the vault counter of its seed owner, freeing
+/// a slot under `MAX_VAULTS_PER_OWNER`.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner
+/// 1. `[writable]` vault PDA account
+/// 2. `[writable]` seed owner's vault counter PDA
+///    `[b"vault_counter", seed owner]`
+/// 3. `[]` instructions sysvar (optional, required for allow-listed CPI closes
+///    and single-op-per-transaction and no-flash vaults)
+/// 4. `[signer]` escrow counterparty (optional, after the sysvar if passed,
+///    required for escrow vaults)
+/// 5. `[signer]` cosigner (optional, after the sysvar if passed, required
+///    above the single signature limit)
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    bump: u8,
+    force: bool,
+    grace_slots: u64,
+) -> ProgramResult {
+    let [owner, vault, counter, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate(program_id, owner, vault)?;
+    withdraw::verify_address(program_id, vault, bump)?;
+    withdraw::verify_stored_address(program_id, vault)?;
+    withdraw::check_caller(vault, remaining.first())?;
+    withdraw::check_single_op(program_id, vault, remaining.first())?;
+    withdraw::check_no_flash(program_id, vault, remaining.first())?;
+    withdraw::check_unlocked(vault)?;
+    withdraw::check_vault_type(vault, remaining)?;
+    withdraw::check_cosigner(vault, vault.lamports(), remaining)?;
+
+    let rent = Rent::get()?;
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    let tracked = vault_state.amount();
+    let seed_owner = *vault_state.seed_owner();
+    let backing = vault_backing(vault, &rent)? as VaultAmount;
+    if tracked != backing {
+        if !force {
+            return Err(VaultError::TrackedAmountMismatch.into());
+        }
+        log!(
+            "VAULT_CLOSE forced tracked={} backing={} delta={}",
+            tracked,
+            backing,
+            tracked.abs_diff(backing)
+        );
+    }
+
+    initialize::uncount_vault(program_id, &seed_owner, counter)?;
+
+    events::record(vault, "close")?;
+
+    // SAFETY: no active borrows of vault data at this point
+    unsafe { VaultMut::from_account_unchecked(vault).mark_closed() };
+
+    // Shrink to a tombstone and keep only its rent, if asked to
+    let kept = if grace_slots > 0 {
+        vault.resize(Tombstone::LEN)?;
+        // SAFETY: the data borrow above is no longer used
+        let data = unsafe { vault.borrow_unchecked_mut() };
+        Tombstone::write(data, owner.address(), Clock::get()?.slot, grace_slots);
+        rent.try_minimum_balance(Tombstone::LEN)?
+    } else {
+        0
+    };
+
+    let lamports = vault
+        .lamports()
+        .checked_sub(kept)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    owner.set_lamports(
+        owner
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+    );
+    vault.set_lamports(kept);
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use pinocchio::{cpi::Signer, error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_system::create_account_with_minimum_balance_signed;
+
+use crate::instructions::deposit;
+use crate::state::receipt::{
+    create_receipt_address, find_receipt_address, receipt_signer_seeds, Receipt,
+};
+use crate::utils::helpers::system_program_check;
+
+/// Process contribute instruction
+///
+/// Same as deposit, but any signer may fund the vault, not only its owner.
+/// The credited amount is added to the contributor's receipt for the vault,
+/// created on their first contribution, which bounds what `Refund` may
+/// return to them.
+///
+/// Accounts:
+/// 0. `[signer, writable]` contributor
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program
+/// 3. `[writable]` contributor's receipt PDA `[b"receipt", vault, contributor]`
+/// 4. `[]` config PDA account (required if the vault charges a deposit fee)
+/// 5. `[writable]` treasury named by the config (required with the config)
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    amount: u64,
+    flags: u8,
+    category: Option<u8>,
+) -> ProgramResult {
+    let [contributor, vault, system_program, receipt, fee_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    deposit::validate_third_party(program_id, contributor, vault)?;
+
+    let credited = deposit::credit(
+        program_id,
+        contributor,
+        vault,
+        amount,
+        flags,
+        category,
+        fee_accounts,
+    )?;
+
+    add_to_receipt(program_id, contributor, vault, receipt, credited)
+}
+
+/// Add `amount` to the receipt of `contributor` in `vault`, creating it at
+/// the contributor's expense if this is their first contribution
+fn add_to_receipt(
+    program_id: &Address,
+    contributor: &AccountView,
+    vault: &AccountView,
+    receipt: &AccountView,
+    amount: u64,
+) -> ProgramResult {
+    if receipt.owned_by(&pinocchio_system::ID) && receipt.is_data_empty() {
+        let (expected, bump) =
+            find_receipt_address(vault.address(), contributor.address(), program_id);
+        if receipt.address() != &expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let bump_bytes = [bump];
+        let seeds = receipt_signer_seeds(vault.address(), contributor.address(), &bump_bytes);
+        let signers = [Signer::from(seeds.as_slice())];
+        create_account_with_minimum_balance_signed(
+            receipt,
+            Receipt::LEN,
+            program_id,
+            contributor,
+            None,
+            &signers,
+        )?;
+
+        // SAFETY: the account was just created, no active borrows
+        Receipt::write(unsafe { receipt.borrow_unchecked_mut() }, amount, bump);
+        return Ok(());
+    }
+
+    let (refundable, bump) = checked_receipt(program_id, vault, contributor, receipt)?;
+    let refundable = refundable
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    // SAFETY: the read view in `checked_receipt` is no longer used
+    Receipt::write(unsafe { receipt.borrow_unchecked_mut() }, refundable, bump);
+
+    Ok(())
+}
+
+/// Validate that `receipt` is the receipt of `depositor` in `vault` and
+/// return its refundable amount and bump
+pub(crate) fn checked_receipt(
+    program_id: &Address,
+    vault: &AccountView,
+    depositor: &AccountView,
+    receipt: &AccountView,
+) -> Result<(u64, u8), ProgramError> {
+    if !receipt.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // SAFETY: no active borrows of receipt data at this point
+    let receipt_state = unsafe { Receipt::from_account(receipt) }?;
+    let bump = receipt_state.bump();
+    if create_receipt_address(vault.address(), depositor.address(), bump, program_id).as_ref()
+        != Some(receipt.address())
+    {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok((receipt_state.refundable(), bump))
+}
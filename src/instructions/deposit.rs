@@ -1,7 +1,26 @@
-use pinocchio::{AccountView, Address, ProgramResult};
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 use pinocchio_system::instructions::Transfer;
+use solana_program_log::log;
 
-use crate::state::vault::Vault;
+use crate::error::VaultError;
+use crate::instructions::set_options::OPTION_WHOLE_UNIT_DEPOSITS;
+use crate::instructions::withdraw;
+use crate::instructions::withdraw_percent_of_actual::MAX_BPS;
+use crate::state::config::Config;
+use crate::state::vault::{Vault, VaultAmount, VaultMut};
+use crate::utils::events;
+use crate::utils::helpers::{owner_account_check, system_program_check};
+
+/// Deposit flag: reject the deposit if it would leave the depositor below
+/// the rent-exempt minimum of a data-less account
+pub const FLAG_PRESERVE_PAYER_RENT: u8 = 1 << 0;
+
+/// All deposit flags understood by this program
+pub const FLAGS_MASK: u8 = FLAG_PRESERVE_PAYER_RENT;
+
+/// Lamports in one SOL, the denomination whole-unit vaults accept
+pub const WHOLE_UNIT_LAMPORTS: u64 = 1_000_000_000;
 
 /// Process deposit instruction
 ///
@@ -9,44 +28,246 @@ use crate::state::vault::Vault;
 /// 0. `[signer, writable]` owner
 /// 1. `[writable]` vault PDA account
 /// 2. `[]` system_program
-pub fn handler(program_id: &Address, accounts: &[AccountView], amount: u64) -> ProgramResult {
-    let [owner, vault, _system_program] = accounts else {
-        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+/// 3. `[]` config PDA account (required if the vault charges a deposit fee)
+/// 4. `[writable]` treasury named by the config (required with the config)
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    amount: u64,
+    flags: u8,
+    category: Option<u8>,
+) -> ProgramResult {
+    let [owner, vault, system_program, fee_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Validate owner is signer
-    assert!(owner.is_signer(), "Owner must be signer");
+    system_program_check(system_program)?;
+
+    // Validate owner is a signable account and signed
+    owner_account_check(owner)?;
+    if !owner.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
 
     // Validate vault is owned by our program
-    assert!(
-        vault.owned_by(program_id),
-        "Vault not owned by this program"
-    );
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
 
     // Validate vault discriminator and owner
-    let vault_state = Vault::from_account(vault);
-    assert!(vault_state.owner() == owner.address(), "Owner mismatch");
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account(vault) }?;
+    if vault_state.owner_bytes() != *owner.address().as_array() {
+        return Err(VaultError::OwnerMismatch.into());
+    }
+
+    // Validate the vault is the PDA of its seed owner, stored label and
+    // stored bump, not just any program-owned account that stores this owner
+    withdraw::verify_stored_address(program_id, vault)?;
+
+    let credited = credit(
+        program_id,
+        owner,
+        vault,
+        amount,
+        flags,
+        category,
+        fee_accounts,
+    )?;
+
+    events::log_balance_change(
+        "VAULT_DEPOSIT",
+        owner.address(),
+        credited,
+        // SAFETY: no active borrows of vault data at this point
+        unsafe { Vault::from_account_unchecked(vault) }.amount(),
+    );
+
+    Ok(())
+}
+
+/// Validate that `depositor` is a signable account and signed, and that
+/// `vault` is a vault at the PDA of its seed owner, stored label and stored
+/// bump
+///
+/// The checks `Contribute` and `BatchDeposit` make before [`credit`], where
+/// the depositor need not be the owner.
+pub(crate) fn validate_third_party(
+    program_id: &Address,
+    depositor: &AccountView,
+    vault: &AccountView,
+) -> ProgramResult {
+    owner_account_check(depositor)?;
+    if !depositor.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    validate_vault(program_id, vault)
+}
+
+/// Validate that `vault` is a program-owned vault at the PDA of its seed
+/// owner, stored label and stored bump
+pub(crate) fn validate_vault(program_id: &Address, vault: &AccountView) -> ProgramResult {
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    unsafe { Vault::from_account(vault) }?;
+    withdraw::verify_stored_address(program_id, vault)
+}
+
+/// Transfer `amount` lamports from `depositor` into an already validated
+/// vault and update the stored amount and depositor statistics
+///
+/// Deposits are rejected until the vault's deposit delay has elapsed since
+/// its creation slot, when they would take the tracked amount above the
+/// vault's nonzero maximum balance, and, on whole-unit vaults, when they are
+/// not a multiple of 1 SOL. If the client tagged the deposit with a category, it
+/// is logged as `VAULT_CATEGORY category=<u8> amount=<u64>` for off-chain
+/// budgeting.
+///
+/// A vault with a deposit fee sends `amount * fee_bps / 10_000`, rounded
+/// down, to the config's treasury and credits the rest. `fee_accounts` must
+/// then start with the config PDA and the treasury. Returns the credited
+/// amount.
+pub(crate) fn credit(
+    program_id: &Address,
+    depositor: &AccountView,
+    vault: &AccountView,
+    amount: u64,
+    flags: u8,
+    category: Option<u8>,
+    fee_accounts: &[AccountView],
+) -> Result<u64, ProgramError> {
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    if vault_state.is_paused() {
+        return Err(VaultError::Paused.into());
+    }
 
-    // Transfer SOL from owner to vault
+    // Enforce the post-creation cooldown, if configured
+    let delay = vault_state.deposit_delay_slots();
+    if delay > 0 && Clock::get()?.slot < vault_state.created_slot().saturating_add(delay) {
+        return Err(VaultError::VaultTooNew.into());
+    }
+
+    // Optionally keep the depositor's own account rent-exempt
+    if flags & FLAG_PRESERVE_PAYER_RENT != 0 {
+        let minimum = Rent::get()?.try_minimum_balance(0)?;
+        let remaining = depositor.lamports().saturating_sub(amount);
+        if remaining < minimum {
+            return Err(VaultError::PayerWouldLoseRentExemption.into());
+        }
+    }
+
+    // Enforce the whole-SOL denomination, if enabled
+    if vault_state.options() & OPTION_WHOLE_UNIT_DEPOSITS != 0
+        && !amount.is_multiple_of(WHOLE_UNIT_LAMPORTS)
+    {
+        return Err(VaultError::NonIntegralAmount.into());
+    }
+
+    // Enforce the vault's minimum deposit, if configured
+    if amount < vault_state.min_deposit() {
+        return Err(VaultError::BelowMinDeposit.into());
+    }
+
+    // Split off the deposit fee, if configured
+    let fee_bps = vault_state.deposit_fee_bps();
+    let fee = (amount as u128 * fee_bps as u128 / MAX_BPS as u128) as u64;
+    let net = amount
+        .checked_sub(fee)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    // Enforce the vault's maximum balance, if configured
+    let new_amount = vault_state
+        .amount()
+        .checked_add(net as VaultAmount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let max_balance = vault_state.max_balance();
+    if max_balance > 0 && new_amount > max_balance as VaultAmount {
+        return Err(VaultError::MaxBalanceExceeded.into());
+    }
+    let deposit_count = vault_state
+        .deposit_count()
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    // Transfer the fee from depositor to treasury
+    if fee_bps > 0 {
+        let (treasury, _) = fee_treasury(program_id, fee_accounts)?;
+        if fee > 0 {
+            Transfer {
+                from: depositor,
+                to: treasury,
+                lamports: fee,
+            }
+            .invoke()?;
+        }
+    }
+
+    // Transfer SOL from depositor to vault
+    let vault_lamports_before = vault.lamports();
     Transfer {
-        from: owner,
+        from: depositor,
         to: vault,
-        lamports: amount,
+        lamports: net,
     }
     .invoke()?;
 
-    // Update the stored amount
-    // SAFETY: no active borrows of vault data at this point
-    let data = unsafe { vault.borrow_unchecked_mut() };
-    let current_amount = u64::from_le_bytes(
-        data[Vault::AMOUNT_OFFSET..Vault::AMOUNT_OFFSET + 8]
-            .try_into()
-            .unwrap(),
-    );
-    let new_amount = current_amount
-        .checked_add(amount)
-        .expect("Deposit overflow");
-    data[Vault::AMOUNT_OFFSET..Vault::AMOUNT_OFFSET + 8].copy_from_slice(&new_amount.to_le_bytes());
+    // Defense in depth: only track what actually arrived
+    if vault.lamports().checked_sub(vault_lamports_before) != Some(net) {
+        return Err(VaultError::TransferMismatch.into());
+    }
 
-    Ok(())
+    // Update the stored amount and deposit count
+    // SAFETY: the read view above is no longer used
+    let mut vault_state = unsafe { VaultMut::from_account_unchecked(vault) };
+    vault_state.set_amount(new_amount);
+    vault_state.set_deposit_count(deposit_count);
+
+    // Count a depositor change only when this deposit comes from a different
+    // key than the previous one
+    if vault_state.view().last_depositor() != depositor.address() {
+        let new_changes = vault_state
+            .view()
+            .depositor_changes()
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        vault_state.set_depositor_changes(new_changes);
+        vault_state.set_last_depositor(depositor.address());
+    }
+
+    if let Some(category) = category {
+        log!("VAULT_CATEGORY category={} amount={}", category, amount);
+    }
+
+    events::record(vault, "deposit")?;
+
+    Ok(net)
+}
+
+/// Return the treasury and the global config from trailing
+/// `[config, treasury]` accounts, checking the treasury is the one the
+/// config names
+pub(crate) fn fee_treasury<'a>(
+    program_id: &Address,
+    fee_accounts: &'a [AccountView],
+) -> Result<(&'a AccountView, Config<'a>), ProgramError> {
+    let [config, treasury, ..] = fee_accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Only `InitializeConfig` writes a config, and only at the config PDA
+    if !config.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    // SAFETY: no active borrows of config data at this point
+    let config = unsafe { Config::from_account(config) }?;
+    if config.treasury() != treasury.address() {
+        return Err(VaultError::TreasuryMismatch.into());
+    }
+
+    Ok((treasury, config))
 }
@@ -0,0 +1,40 @@
+//! Instruction discriminators, the first byte of each instruction's data
+
+pub const INITIALIZE: u8 = 0;
+pub const DEPOSIT: u8 = 1;
+pub const WITHDRAW: u8 = 2;
+pub const CLOSE_VAULT: u8 = 3;
+pub const TRANSFER_OWNERSHIP: u8 = 4;
+pub const WITHDRAW_ALL: u8 = 5;
+pub const INITIALIZE_IF_NEEDED: u8 = 6;
+pub const WITHDRAW_TO: u8 = 7;
+pub const CONTRIBUTE: u8 = 8;
+pub const WITHDRAW_WITH_REBATE: u8 = 9;
+pub const SET_REBATE: u8 = 10;
+pub const SWEEP_DUST: u8 = 11;
+pub const SET_ALLOWED_CALLERS: u8 = 12;
+pub const VERIFY: u8 = 13;
+pub const WITHDRAW_PERCENT_OF_ACTUAL: u8 = 14;
+pub const SET_DEPOSIT_DELAY: u8 = 15;
+pub const INITIALIZE_AUTO_BUMP: u8 = 16;
+pub const SET_OPTIONS: u8 = 17;
+pub const WITHDRAW_SPLIT: u8 = 18;
+pub const ALLOCATE_VAULT: u8 = 19;
+pub const INITIALIZE_ALLOCATED: u8 = 20;
+pub const SET_UNLOCK_SLOT: u8 = 21;
+pub const PING: u8 = 22;
+pub const ATTEST: u8 = 23;
+pub const QUERY_BATCH: u8 = 24;
+pub const INITIALIZE_CONFIG: u8 = 25;
+pub const SET_PAUSED: u8 = 26;
+pub const SET_AUTHORITY: u8 = 27;
+pub const BATCH_DEPOSIT: u8 = 28;
+pub const SET_DEPOSIT_FEE: u8 = 29;
+pub const REFUND: u8 = 30;
+pub const REOPEN: u8 = 31;
+pub const REAP_TOMBSTONE: u8 = 32;
+pub const SET_COSIGNER: u8 = 33;
+pub const ADMIN_RECONCILE: u8 = 34;
+pub const RELABEL_VAULT: u8 = 35;
+#[cfg(feature = "test-hooks")]
+pub const SET_STATE_RAW: u8 = 0xFF;
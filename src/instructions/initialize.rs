@@ -1,58 +1,329 @@
-use pinocchio::{
-    cpi::{Seed, Signer},
-    AccountView, Address, ProgramResult,
-};
-use pinocchio_system::create_account_with_minimum_balance_signed;
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::{cpi::Signer, error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_system::{create_account_with_minimum_balance_signed, instructions::Transfer};
+use solana_program_log::log;
 
-use crate::state::vault::{Vault, VAULT_DISCRIMINATOR};
+use crate::error::VaultError;
+use crate::instructions::deposit;
+use crate::state::vault::{
+    create_vault_address, find_vault_address, vault_signer_seeds, Vault, VaultData, VaultMut,
+    VaultType, PROGRAM_VERSION, VAULT_DISCRIMINATOR, VAULT_LABEL_LEN,
+};
+use crate::state::vault_counter::{
+    create_vault_counter_address, find_vault_counter_address, vault_counter_signer_seeds,
+    VaultCounter, MAX_VAULTS_PER_OWNER,
+};
+use crate::utils::helpers::{owner_account_check, system_program_check, uninitialized_check};
 
 /// Process initialize instruction
 ///
+/// The vault PDA is `[b"vault", owner, label]`. An owner can hold one vault
+/// per label; the default (all zero) label is the owner's original vault.
+/// A nonzero `max_balance` caps the amount deposits may bring the vault to.
+/// `vault_type` selects the vault's rule set; escrow and custody vaults also
+/// record `party`, which must be set and differ from the owner. A nonzero
+/// `min_deposit` rejects smaller deposits.
+///
+/// The vault is counted in the owner's vault counter, see [`count_vault`].
+/// Passing the global config and its treasury charges the config's creation
+/// fee on top of rent, see [`charge_creation_fee`].
+///
 /// Accounts:
 /// 0. `[signer, writable]` owner / payer
 /// 1. `[writable]` vault PDA account
 /// 2. `[]` system_program
-pub fn handler(program_id: &Address, accounts: &[AccountView], bump: u8) -> ProgramResult {
-    let [payer, vault, _system_program] = accounts else {
-        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+/// 3. `[writable]` owner's vault counter PDA `[b"vault_counter", owner]`
+/// 4. `[]` config PDA account (optional)
+/// 5. `[writable]` treasury named by the config (required with the config)
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    bump: u8,
+    label: &[u8; VAULT_LABEL_LEN],
+    max_balance: u64,
+    vault_type: VaultType,
+    party: &[u8; 32],
+    min_deposit: u64,
+) -> ProgramResult {
+    let [payer, vault, system_program, counter, fee_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Validate payer is signer
-    assert!(payer.is_signer(), "Payer must be signer");
+    system_program_check(system_program)?;
+
+    // Validate payer is a signable account and signed
+    owner_account_check(payer)?;
+    if !payer.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    // Reject an existing vault, or any account that is no longer a bare
+    // system account, up front instead of letting the create CPI fail
+    // opaquely
+    uninitialized_check(vault)?;
+
+    let party = Address::from(*party);
+    if vault_type.has_party() && (party == Address::default() || &party == payer.address()) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    verify_bump(program_id, payer, vault, label, bump)?;
+
+    count_vault(program_id, payer, counter)?;
+    charge_creation_fee(program_id, payer, vault, fee_accounts)?;
+    create(program_id, payer, vault, label, bump)?;
+    // SAFETY: no active borrows of vault data at this point
+    let mut vault_state = unsafe { VaultMut::from_account_unchecked(vault) };
+    vault_state.set_max_balance(max_balance);
+    vault_state.set_vault_type(vault_type, &party);
+    vault_state.set_min_deposit(min_deposit);
+
+    Ok(())
+}
 
+/// Verify that `vault` is the PDA of `payer` and `label` at the
+/// client-supplied `bump`, logging the canonical bump on mismatch
+pub(crate) fn verify_bump(
+    program_id: &Address,
+    payer: &AccountView,
+    vault: &AccountView,
+    label: &[u8; VAULT_LABEL_LEN],
+    bump: u8,
+) -> ProgramResult {
     // Verify the vault PDA matches expected derivation
     // The client derives find_program_address off-chain and passes the bump
+    if create_vault_address(payer.address(), label, bump, program_id).as_ref()
+        != Some(vault.address())
+    {
+        // Only pay for the canonical bump search on the failure path
+        let (_, expected) = find_vault_address(payer.address(), label, program_id);
+        log!(
+            "VAULT_INIT bump mismatch passed={} expected={}",
+            bump,
+            expected
+        );
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(())
+}
+
+/// Count a new vault of `owner` in their vault counter, creating the
+/// counter at the owner's expense with their first vault
+///
+/// Fails with `VaultLimitReached` if the owner already has
+/// `MAX_VAULTS_PER_OWNER` vaults. `CloseVault` frees the slot again.
+pub(crate) fn count_vault(
+    program_id: &Address,
+    owner: &AccountView,
+    counter: &AccountView,
+) -> ProgramResult {
+    if counter.owned_by(&pinocchio_system::ID) && counter.is_data_empty() {
+        let (expected, bump) = find_vault_counter_address(owner.address(), program_id);
+        if counter.address() != &expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let bump_bytes = [bump];
+        let seeds = vault_counter_signer_seeds(owner.address(), &bump_bytes);
+        let signers = [Signer::from(seeds.as_slice())];
+        create_account_with_minimum_balance_signed(
+            counter,
+            VaultCounter::LEN,
+            program_id,
+            owner,
+            None,
+            &signers,
+        )?;
+
+        // SAFETY: the account was just created, no active borrows
+        VaultCounter::write(unsafe { counter.borrow_unchecked_mut() }, 1, bump);
+        return Ok(());
+    }
+
+    let (count, bump) = checked_counter(program_id, owner.address(), counter)?;
+    if count >= MAX_VAULTS_PER_OWNER {
+        return Err(VaultError::VaultLimitReached.into());
+    }
+    // SAFETY: the read view in `checked_counter` is no longer used
+    VaultCounter::write(unsafe { counter.borrow_unchecked_mut() }, count + 1, bump);
+
+    Ok(())
+}
+
+/// Remove a closed vault of `owner` from their vault counter
+///
+/// Vaults created before the counter existed were never counted, so a
+/// counter that was never created is left alone rather than rejected.
+pub(crate) fn uncount_vault(
+    program_id: &Address,
+    owner: &Address,
+    counter: &AccountView,
+) -> ProgramResult {
+    if counter.owned_by(&pinocchio_system::ID) && counter.is_data_empty() {
+        if counter.address() != &find_vault_counter_address(owner, program_id).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        return Ok(());
+    }
+
+    let (count, bump) = checked_counter(program_id, owner, counter)?;
+    // SAFETY: the read view in `checked_counter` is no longer used
+    VaultCounter::write(
+        unsafe { counter.borrow_unchecked_mut() },
+        count.saturating_sub(1),
+        bump,
+    );
+
+    Ok(())
+}
+
+/// Validate that `counter` is the vault counter of `owner` and return its
+/// vault count and bump
+fn checked_counter(
+    program_id: &Address,
+    owner: &Address,
+    counter: &AccountView,
+) -> Result<(u32, u8), ProgramError> {
+    if !counter.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // SAFETY: no active borrows of counter data at this point
+    let counter_state = unsafe { VaultCounter::from_account(counter) }?;
+    let bump = counter_state.bump();
+    if create_vault_counter_address(owner, bump, program_id).as_ref() != Some(counter.address()) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok((counter_state.vault_count(), bump))
+}
+
+/// Transfer the global config's creation fee from `payer` to the treasury
+///
+/// `fee_accounts` are the optional trailing `[config, treasury]` accounts of
+/// the initialize instructions; without them no fee is charged. The payer
+/// must cover the fee plus whatever rent `vault` still lacks, so an
+/// under-funded payer fails with `InsufficientFunds` before any transfer
+/// instead of partway through account creation.
+pub(crate) fn charge_creation_fee(
+    program_id: &Address,
+    payer: &AccountView,
+    vault: &AccountView,
+    fee_accounts: &[AccountView],
+) -> ProgramResult {
+    if fee_accounts.is_empty() {
+        return Ok(());
+    }
+
+    let (treasury, config) = deposit::fee_treasury(program_id, fee_accounts)?;
+    let fee = config.creation_fee();
+
+    let rent_due = Rent::get()?
+        .try_minimum_balance(Vault::LEN)?
+        .saturating_sub(vault.lamports());
+    let required = rent_due
+        .checked_add(fee)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    if payer.lamports() < required {
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    if fee > 0 {
+        Transfer {
+            from: payer,
+            to: treasury,
+            lamports: fee,
+        }
+        .invoke()?;
+    }
+
+    Ok(())
+}
+
+/// Create the vault PDA at an already verified `bump` and write its
+/// initial state
+pub(crate) fn create(
+    program_id: &Address,
+    payer: &AccountView,
+    vault: &AccountView,
+    label: &[u8; VAULT_LABEL_LEN],
+    bump: u8,
+) -> ProgramResult {
+    allocate(program_id, payer, vault, label, bump)?;
+    write_initial_state(payer, vault, label, bump)
+}
+
+/// Create the vault PDA at an already verified `bump`, sized and owned by
+/// the program but with its data left zeroed
+pub(crate) fn allocate(
+    program_id: &Address,
+    payer: &AccountView,
+    vault: &AccountView,
+    label: &[u8; VAULT_LABEL_LEN],
+    bump: u8,
+) -> ProgramResult {
     let bump_bytes = [bump];
-    let seeds: [Seed; 3] = [
-        Seed::from(b"vault" as &[u8]),
-        Seed::from(payer.address().as_ref()),
-        Seed::from(&bump_bytes as &[u8]),
-    ];
+    let seeds = vault_signer_seeds(payer.address(), label, &bump_bytes);
     let signers = [Signer::from(seeds.as_slice())];
 
     // Create the vault account (PDA signed)
-    create_account_with_minimum_balance_signed(
-        vault,
-        Vault::LEN,
-        program_id,
-        payer,
-        None,
-        &signers,
-    )?;
-
-    // Write vault data
-    // SAFETY: we just created this account, no active borrows
-    let data = unsafe { vault.borrow_unchecked_mut() };
-
-    // Write discriminator
-    data[Vault::DISCRIMINATOR_OFFSET..Vault::DISCRIMINATOR_OFFSET + 8]
-        .copy_from_slice(&VAULT_DISCRIMINATOR);
-
-    // Write owner
-    data[Vault::OWNER_OFFSET..Vault::OWNER_OFFSET + 32].copy_from_slice(payer.address().as_ref());
-
-    // Write initial amount (0)
-    data[Vault::AMOUNT_OFFSET..Vault::AMOUNT_OFFSET + 8].copy_from_slice(&0u64.to_le_bytes());
+    create_account_with_minimum_balance_signed(vault, Vault::LEN, program_id, payer, None, &signers)
+}
+
+/// Write the initial state of a freshly allocated vault owned by `payer`
+pub(crate) fn write_initial_state(
+    payer: &AccountView,
+    vault: &AccountView,
+    label: &[u8; VAULT_LABEL_LEN],
+    bump: u8,
+) -> ProgramResult {
+    // SAFETY: the account was just allocated, no active borrows
+    let mut vault_state = unsafe { VaultMut::from_account_unchecked(vault) };
+
+    // Write discriminator, owner and initial amount (0)
+    vault_state.set_header(&VaultData {
+        discriminator: VAULT_DISCRIMINATOR,
+        owner: *payer.address().as_array(),
+        amount: 0,
+    });
+
+    // Write empty depositor statistics, initial rebate (0, disabled),
+    // initial sequence number (0, no events yet) and empty allowed CPI
+    // caller list
+    vault_state.set_last_depositor(&Address::default());
+    vault_state.set_depositor_changes(0);
+    vault_state.set_rebate_lamports(0);
+    vault_state.set_seq(0);
+    vault_state.set_allowed_callers(&[]);
+
+    // Write creation slot and initial deposit delay (0, disabled)
+    vault_state.set_created_slot(Clock::get()?.slot);
+    vault_state.set_deposit_delay_slots(0);
+
+    // Write seed owner and bump, initial options (none), the creating
+    // program version and the label the PDA was derived with
+    vault_state.set_seed_owner(payer.address());
+    vault_state.set_bump(bump);
+    vault_state.set_options(0);
+    vault_state.set_created_by_version(PROGRAM_VERSION);
+    vault_state.set_label(label);
+
+    // Write initial unlock slot (0, unlocked), initial maximum balance
+    // (0, unlimited), paused flag (unpaused), vault type (standard, no party),
+    // authority (the owner), activity counts (0), minimum deposit (0, none),
+    // deposit fee (0, none) and single signature limit (0, no cosigner)
+    vault_state.set_unlock_slot(0);
+    vault_state.set_max_balance(0);
+    vault_state.set_paused(false);
+    vault_state.set_vault_type(VaultType::Standard, &Address::default());
+    vault_state.set_authority(payer.address());
+    vault_state.set_deposit_count(0);
+    vault_state.set_withdraw_count(0);
+    vault_state.set_min_deposit(0);
+    vault_state.set_deposit_fee_bps(0);
+    vault_state.set_cosigner(0, &Address::default());
 
     Ok(())
 }
@@ -0,0 +1,54 @@
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::error::VaultError;
+use crate::instructions::initialize;
+use crate::state::vault::{Vault, DEFAULT_VAULT_LABEL};
+use crate::utils::helpers::{owner_account_check, system_program_check};
+
+/// Process initialize-allocated instruction
+///
+/// Second phase of two-phase creation: writes the initial state into a
+/// vault reserved by `AllocateVault`. The account must be program-owned,
+/// vault-sized and still have a zeroed discriminator. Rent was paid by
+/// `AllocateVault`, so only the creation fee is charged here, and only when
+/// the trailing accounts are passed.
+///
+/// Accounts:
+/// 0. `[signer]` owner, the key the vault was allocated for (writable when
+///    paying the creation fee)
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program (optional, required with the config)
+/// 3. `[]` config PDA account (optional, charges the creation fee)
+/// 4. `[writable]` treasury named by the config (required with the config)
+pub fn handler(program_id: &Address, accounts: &[AccountView], bump: u8) -> ProgramResult {
+    let [owner, vault, fee_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    owner_account_check(owner)?;
+    if !owner.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    if vault.data_len() != Vault::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    if unsafe { Vault::from_account_unchecked(vault) }.discriminator() != [0; 8] {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    initialize::verify_bump(program_id, owner, vault, &DEFAULT_VAULT_LABEL, bump)?;
+
+    if let [system_program, fee_accounts @ ..] = fee_accounts {
+        system_program_check(system_program)?;
+        initialize::charge_creation_fee(program_id, owner, vault, fee_accounts)?;
+    }
+
+    initialize::write_initial_state(owner, vault, &DEFAULT_VAULT_LABEL, bump)
+}
@@ -0,0 +1,56 @@
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::error::VaultError;
+use crate::instructions::initialize;
+use crate::state::vault::{create_vault_address, DEFAULT_VAULT_LABEL};
+use crate::utils::helpers::{owner_account_check, system_program_check, uninitialized_check};
+
+/// Number of bumps tried, counting down from 255, before giving up
+///
+/// Each attempt is a `create_program_address` syscall, so this caps the
+/// compute spent searching. A valid bump lies within the first 16 with
+/// probability 1 - 2^-16; clients that hit the limit can fall back to
+/// `Initialize` with a bump from `find_program_address`.
+pub const MAX_BUMP_ATTEMPTS: u8 = 16;
+
+/// Process initialize-auto-bump instruction
+///
+/// Same as initialize, but the program finds the canonical bump itself so
+/// the client does not need to run `find_program_address`.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner / payer
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program
+/// 3. `[writable]` owner's vault counter PDA `[b"vault_counter", owner]`
+/// 4. `[]` config PDA account (optional, charges the creation fee)
+/// 5. `[writable]` treasury named by the config (required with the config)
+pub fn handler(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [payer, vault, system_program, counter, fee_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    owner_account_check(payer)?;
+    if !payer.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    uninitialized_check(vault)?;
+
+    // The first bump that lands off the curve is the canonical one
+    let bump = (u8::MAX - (MAX_BUMP_ATTEMPTS - 1)..=u8::MAX)
+        .rev()
+        .find_map(|bump| {
+            create_vault_address(payer.address(), &DEFAULT_VAULT_LABEL, bump, program_id)
+                .map(|address| (address, bump))
+        })
+        .filter(|(address, _)| address == vault.address())
+        .map(|(_, bump)| bump)
+        .ok_or(ProgramError::InvalidSeeds)?;
+
+    initialize::count_vault(program_id, payer, counter)?;
+    initialize::charge_creation_fee(program_id, payer, vault, fee_accounts)?;
+    initialize::create(program_id, payer, vault, &DEFAULT_VAULT_LABEL, bump)
+}
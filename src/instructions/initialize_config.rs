@@ -0,0 +1,113 @@
+use pinocchio::{cpi::Signer, error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_system::create_account_with_minimum_balance_signed;
+
+use crate::error::VaultError;
+use crate::state::config::{
+    config_signer_seeds, find_config_address, Config, CONFIG_DISCRIMINATOR,
+};
+use crate::utils::helpers::system_program_check;
+
+/// BPF upgradeable loader program ID
+pub const BPF_LOADER_UPGRADEABLE_ID: Address = Address::new_from_array([
+    0x02, 0xa8, 0xf6, 0x91, 0x4e, 0x88, 0xa1, 0xb0, 0xe2, 0x10, 0x15, 0x3e, 0xf7, 0x63, 0xae, 0x2b,
+    0x00, 0xc2, 0xb9, 0x3d, 0x16, 0xc1, 0x24, 0xd2, 0xc0, 0x53, 0x7a, 0x10, 0x04, 0x80, 0x00, 0x00,
+]); // BPFLoaderUpgradeab1e11111111111111111111111
+
+/// `UpgradeableLoaderState::ProgramData` tag
+const PROGRAM_DATA_TAG: u32 = 3;
+
+/// Length of the `ProgramData` header: tag (u32), slot (u64) and
+/// `Option<Pubkey>` upgrade authority (1 + 32)
+const PROGRAM_DATA_HEADER_LEN: usize = 4 + 8 + 1 + 32;
+
+/// Process initialize-config instruction
+///
+/// Creates the global config PDA `[b"config"]` once, with the admin,
+/// treasury and creation fee supplied. Only the program's upgrade
+/// authority, as recorded in its `ProgramData` account, may call it; a
+/// second call fails because the account already exists.
+///
+/// Accounts:
+/// 0. `[signer, writable]` upgrade authority / payer
+/// 1. `[writable]` config PDA account
+/// 2. `[]` program data account of this program
+/// 3. `[]` system_program
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    admin: &[u8; 32],
+    treasury: &[u8; 32],
+    creation_fee: u64,
+) -> ProgramResult {
+    let [authority, config, program_data, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    if !authority.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    check_upgrade_authority(program_id, authority, program_data)?;
+
+    if config.owned_by(program_id) {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let (expected, bump) = find_config_address(program_id);
+    if &expected != config.address() {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let bump_bytes = [bump];
+    let seeds = config_signer_seeds(&bump_bytes);
+    let signers = [Signer::from(seeds.as_slice())];
+    create_account_with_minimum_balance_signed(
+        config,
+        Config::LEN,
+        program_id,
+        authority,
+        None,
+        &signers,
+    )?;
+
+    // SAFETY: the account was just allocated, no active borrows
+    let data = unsafe { config.borrow_unchecked_mut() };
+    data[Config::DISCRIMINATOR_OFFSET..Config::DISCRIMINATOR_OFFSET + 8]
+        .copy_from_slice(&CONFIG_DISCRIMINATOR);
+    data[Config::ADMIN_OFFSET..Config::ADMIN_OFFSET + 32].copy_from_slice(admin);
+    data[Config::TREASURY_OFFSET..Config::TREASURY_OFFSET + 32].copy_from_slice(treasury);
+    data[Config::CREATION_FEE_OFFSET..Config::CREATION_FEE_OFFSET + 8]
+        .copy_from_slice(&creation_fee.to_le_bytes());
+    data[Config::BUMP_OFFSET] = bump;
+
+    Ok(())
+}
+
+/// Require `authority` to be the upgrade authority recorded in this
+/// program's `ProgramData` account
+fn check_upgrade_authority(
+    program_id: &Address,
+    authority: &AccountView,
+    program_data: &AccountView,
+) -> ProgramResult {
+    let (expected, _) =
+        Address::find_program_address(&[program_id.as_ref()], &BPF_LOADER_UPGRADEABLE_ID);
+    if &expected != program_data.address() || !program_data.owned_by(&BPF_LOADER_UPGRADEABLE_ID) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // SAFETY: no active borrows of the program data at this point
+    let data = unsafe { program_data.borrow_unchecked() };
+    if data.len() < PROGRAM_DATA_HEADER_LEN || data[0..4] != PROGRAM_DATA_TAG.to_le_bytes() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // An immutable program (no upgrade authority) cannot initialize config
+    if data[12] != 1 || &data[13..45] != authority.address().as_ref() {
+        return Err(VaultError::NotUpgradeAuthority.into());
+    }
+
+    Ok(())
+}
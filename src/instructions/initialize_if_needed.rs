@@ -0,0 +1,54 @@
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::error::VaultError;
+use crate::instructions::initialize;
+use crate::state::vault::{Vault, DEFAULT_VAULT_LABEL};
+use crate::utils::helpers::{owner_account_check, system_program_check, uninitialized_check};
+
+/// Process initialize-if-needed instruction
+///
+/// `Initialize` for clients that do not know whether the vault exists yet.
+/// If the payer's default vault is already initialized and still owned by
+/// the payer this is a no-op; otherwise it is created as usual. An account
+/// that is program-owned but does not hold valid vault data (for example
+/// one reserved by `AllocateVault`) is rejected rather than treated as
+/// initialized. The vault is only counted, and the creation fee only
+/// charged, when it is created.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner / payer
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program
+/// 3. `[writable]` owner's vault counter PDA `[b"vault_counter", owner]`
+/// 4. `[]` config PDA account (optional, charges the creation fee)
+/// 5. `[writable]` treasury named by the config (required with the config)
+pub fn handler(program_id: &Address, accounts: &[AccountView], bump: u8) -> ProgramResult {
+    let [payer, vault, system_program, counter, fee_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    owner_account_check(payer)?;
+    if !payer.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    initialize::verify_bump(program_id, payer, vault, &DEFAULT_VAULT_LABEL, bump)?;
+
+    if vault.owned_by(program_id) {
+        // Validate the existing data before short-circuiting
+        // SAFETY: no active borrows of vault data at this point
+        let vault_state = unsafe { Vault::from_account(vault) }?;
+        if vault_state.owner_bytes() != *payer.address().as_array() {
+            return Err(VaultError::OwnerMismatch.into());
+        }
+
+        return Ok(());
+    }
+
+    uninitialized_check(vault)?;
+    initialize::count_vault(program_id, payer, counter)?;
+    initialize::charge_creation_fee(program_id, payer, vault, fee_accounts)?;
+    initialize::create(program_id, payer, vault, &DEFAULT_VAULT_LABEL, bump)
+}
@@ -1,58 +1,1173 @@
+pub mod admin_reconcile;
+pub mod allocate_vault;
+pub mod attest;
+pub mod batch_deposit;
+pub mod close;
+pub mod contribute;
 pub mod deposit;
+pub mod discriminator;
 pub mod initialize;
+pub mod initialize_allocated;
+pub mod initialize_auto_bump;
+pub mod initialize_config;
+pub mod initialize_if_needed;
+pub mod ping;
+pub mod query_batch;
+pub mod reap_tombstone;
+pub mod refund;
+pub mod relabel_vault;
+pub mod reopen;
+pub mod set_allowed_callers;
+pub mod set_authority;
+pub mod set_cosigner;
+pub mod set_deposit_delay;
+pub mod set_deposit_fee;
+pub mod set_options;
+pub mod set_paused;
+pub mod set_rebate;
+#[cfg(feature = "test-hooks")]
+pub mod set_state_raw;
+pub mod set_unlock_slot;
+pub mod sweep_dust;
+pub mod transfer_ownership;
+pub mod verify;
 pub mod withdraw;
+pub mod withdraw_all;
+pub mod withdraw_percent_of_actual;
+pub mod withdraw_split;
+pub mod withdraw_to;
+pub mod withdraw_with_rebate;
 
 use pinocchio::error::ProgramError;
 use pinocchio::{AccountView, Address, ProgramResult};
 
+use crate::state::vault::{Vault, VaultType, DEFAULT_VAULT_LABEL, VAULT_LABEL_LEN};
+
+/// Vault program instructions
+///
+/// Each instruction is a one byte discriminator followed by its core fields,
+/// which are required, and then its optional trailing fields, which fall back
+/// to a default when absent and are validated when present. Bytes left over
+/// after the last field the instruction defines are rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum VaultInstruction {
-    /// Initialize a vault. Data: [bump: u8]
-    Initialize { bump: u8 },
-    /// Deposit SOL into the vault. Data: [amount: u64]
-    Deposit { amount: u64 },
-    /// Withdraw SOL from the vault. Data: [amount: u64, bump: u8]
-    Withdraw { amount: u64, bump: u8 },
+    /// Initialize a vault.
+    /// Core: [bump: u8]
+    /// Optional: [label: [u8; 16] (default all zero),
+    /// max_balance: u64 (default 0, unlimited),
+    /// vault_type: u8 (default 0, standard), then for escrow and custody only
+    /// party: [u8; 32] (required), then min_deposit: u64 (default 0, none)]
+    Initialize {
+        bump: u8,
+        label: [u8; VAULT_LABEL_LEN],
+        max_balance: u64,
+        vault_type: VaultType,
+        party: [u8; 32],
+        min_deposit: u64,
+    },
+    /// Deposit SOL into the vault.
+    /// Core: [amount: u64]
+    /// Optional: [flags: u8 (default 0), category: u8 (default none)]
+    Deposit {
+        amount: u64,
+        flags: u8,
+        category: Option<u8>,
+    },
+    /// Withdraw SOL from the vault.
+    /// Core: [amount: u64, bump: u8]
+    /// Optional: [label: [u8; 16] (default all zero)]
+    Withdraw {
+        amount: u64,
+        bump: u8,
+        label: [u8; VAULT_LABEL_LEN],
+    },
+    /// Close the vault, returning all lamports to the owner.
+    /// Core: [bump: u8]
+    /// Optional: [force: u8 (default 0), 0 or 1,
+    /// grace_slots: u64 (default 0, no tombstone)]
+    CloseVault {
+        bump: u8,
+        force: bool,
+        grace_slots: u64,
+    },
+    /// Reassign the vault to a new owner. The PDA address is unchanged.
+    /// Core: [new_owner: [u8; 32]]
+    TransferOwnership { new_owner: [u8; 32] },
+    /// Withdraw the vault's entire tracked amount.
+    /// Core: [bump: u8]
+    WithdrawAll { bump: u8 },
+    /// Initialize the default vault unless it already exists.
+    /// Core: [bump: u8]
+    InitializeIfNeeded { bump: u8 },
+    /// Withdraw SOL from the vault to a recipient account.
+    /// Core: [amount: u64, bump: u8]
+    WithdrawTo { amount: u64, bump: u8 },
+    /// Deposit SOL into any vault from any signer.
+    /// Core: [amount: u64]
+    /// Optional: [flags: u8 (default 0), category: u8 (default none)]
+    Contribute {
+        amount: u64,
+        flags: u8,
+        category: Option<u8>,
+    },
+    /// Withdraw SOL and pay the fee payer the vault's configured rebate.
+    /// Core: [amount: u64, bump: u8]
+    WithdrawWithRebate { amount: u64, bump: u8 },
+    /// Set the fee payer rebate used by `WithdrawWithRebate`.
+    /// Core: [lamports: u64]
+    SetRebate { lamports: u64 },
+    /// Fold untracked lamports above rent into the tracked amount.
+    /// Core: []
+    SweepDust,
+    /// Replace the programs allowed to withdraw via CPI.
+    /// Core: [count: u8, callers: [u8; 32] * count], count <= 4
+    SetAllowedCallers {
+        count: u8,
+        callers: [[u8; 32]; Vault::MAX_ALLOWED_CALLERS],
+    },
+    /// Report the tracked amount and whether it matches the vault's backing
+    /// lamports via return data.
+    Verify,
+    /// Withdraw a share of the vault's backing lamports and reconcile the
+    /// tracked amount to what remains.
+    /// Core: [bps: u16], 0 < bps <= 10_000
+    WithdrawPercentOfActual { bps: u16 },
+    /// Set the number of slots after creation before deposits are accepted.
+    /// Core: [slots: u64]
+    SetDepositDelay { slots: u64 },
+    /// Initialize a vault, finding the canonical bump on-chain.
+    InitializeAutoBump,
+    /// Replace the vault's option bits.
+    /// Core: [options: u8]
+    SetOptions { options: u8 },
+    /// Withdraw SOL and split it across recipients by basis points.
+    /// Core: [amount: u64, bump: u8, count: u8, shares: u16 * count],
+    /// 1 <= count <= 4, shares sum to 10_000
+    WithdrawSplit {
+        amount: u64,
+        bump: u8,
+        count: u8,
+        shares: [u16; withdraw_split::MAX_SPLIT_RECIPIENTS],
+    },
+    /// Create the vault PDA with its data left zeroed, reserving the address.
+    /// Core: [bump: u8]
+    AllocateVault { bump: u8 },
+    /// Write the initial state of a vault created by `AllocateVault`.
+    /// Core: [bump: u8]
+    InitializeAllocated { bump: u8 },
+    /// Set the slot before which withdrawals are rejected.
+    /// Core: [slot: u64]
+    SetUnlockSlot { slot: u64 },
+    /// Report the program version via return data. Takes no accounts.
+    Ping,
+    /// Report an owner-signed snapshot of the vault via return data.
+    Attest,
+    /// Report the owner and amount of up to 16 vaults via return data.
+    QueryBatch,
+    /// Create the global config PDA. Only the upgrade authority may call it.
+    /// Core: [admin: [u8; 32], treasury: [u8; 32], creation_fee: u64]
+    InitializeConfig {
+        admin: [u8; 32],
+        treasury: [u8; 32],
+        creation_fee: u64,
+    },
+    /// Freeze or unfreeze deposits and withdrawals.
+    /// Core: [paused: u8, 0 or 1]
+    SetPaused { paused: bool },
+    /// Hand control of the vault's settings to a new authority.
+    /// Core: [new_authority: [u8; 32]]
+    SetAuthority { new_authority: [u8; 32] },
+    /// Deposit SOL from one signer into several vaults.
+    /// Core: [count: u8, amounts: u64 * count], 1 <= count <= 8
+    BatchDeposit {
+        count: u8,
+        amounts: [u64; batch_deposit::MAX_BATCH_DEPOSITS],
+    },
+    /// Set the share of each deposit sent to the config's treasury. Signed
+    /// by the global config's admin.
+    /// Core: [fee_bps: u16], fee_bps <= 10_000
+    SetDepositFee { fee_bps: u16 },
+    /// Return SOL from the vault to a contributor, up to their receipt.
+    /// Core: [amount: u64, bump: u8]
+    Refund { amount: u64, bump: u8 },
+    /// Restore a vault from the tombstone `CloseVault` left behind.
+    /// Core: [bump: u8]
+    /// Optional: [label: [u8; 16] (default all zero)]
+    Reopen {
+        bump: u8,
+        label: [u8; VAULT_LABEL_LEN],
+    },
+    /// Remove a tombstone, returning its rent to the owner.
+    ReapTombstone,
+    /// Require a cosigner on withdrawals above a limit.
+    /// Core: [single_sig_limit: u64 (0 for no limit), cosigner: [u8; 32]]
+    SetCosigner {
+        single_sig_limit: u64,
+        cosigner: [u8; 32],
+    },
+    /// Set any vault's tracked amount to its backing lamports. Signed by
+    /// the global config's admin.
+    AdminReconcile,
+    /// Move a vault to the PDA of a new label.
+    /// Core: [new_label: [u8; 16], new_bump: u8]
+    RelabelVault {
+        new_label: [u8; VAULT_LABEL_LEN],
+        new_bump: u8,
+    },
+    /// Overwrite raw vault bytes. Only compiled with `test-hooks`.
+    /// Core: [offset: u16, len: u8, bytes: [u8; len]], len <= 32
+    #[cfg(feature = "test-hooks")]
+    SetStateRaw {
+        offset: u16,
+        len: u8,
+        bytes: [u8; set_state_raw::MAX_RAW_WRITE],
+    },
 }
 
 impl VaultInstruction {
     pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
-        if data.is_empty() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
+        let (&discriminator, rest) = data
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let mut reader = Reader(rest);
 
-        Ok(match data[0] {
-            0 => {
-                if data.len() < 2 {
+        let instruction = match discriminator {
+            discriminator::INITIALIZE => {
+                let bump = reader.u8()?;
+                let label = reader.optional_label()?;
+                let max_balance = reader.optional_u64()?;
+                let vault_type = match reader.optional_u8() {
+                    Some(value) => VaultType::try_from(value)?,
+                    None => VaultType::Standard,
+                };
+                let party = if vault_type.has_party() {
+                    reader.take(32)?.try_into().unwrap()
+                } else {
+                    [0u8; 32]
+                };
+                Self::Initialize {
+                    bump,
+                    label,
+                    max_balance,
+                    vault_type,
+                    party,
+                    min_deposit: reader.optional_u64()?,
+                }
+            }
+            discriminator::DEPOSIT => {
+                let (amount, flags, category) = Self::unpack_deposit(&mut reader)?;
+                Self::Deposit {
+                    amount,
+                    flags,
+                    category,
+                }
+            }
+            discriminator::WITHDRAW => Self::Withdraw {
+                amount: reader.u64()?,
+                bump: reader.u8()?,
+                label: reader.optional_label()?,
+            },
+            discriminator::CLOSE_VAULT => Self::CloseVault {
+                bump: reader.u8()?,
+                force: match reader.optional_u8() {
+                    None | Some(0) => false,
+                    Some(1) => true,
+                    Some(_) => return Err(ProgramError::InvalidInstructionData),
+                },
+                grace_slots: reader.optional_u64()?,
+            },
+            discriminator::TRANSFER_OWNERSHIP => Self::TransferOwnership {
+                new_owner: reader.take(32)?.try_into().unwrap(),
+            },
+            discriminator::WITHDRAW_ALL => Self::WithdrawAll { bump: reader.u8()? },
+            discriminator::INITIALIZE_IF_NEEDED => Self::InitializeIfNeeded { bump: reader.u8()? },
+            discriminator::WITHDRAW_TO => Self::WithdrawTo {
+                amount: reader.u64()?,
+                bump: reader.u8()?,
+            },
+            discriminator::CONTRIBUTE => {
+                let (amount, flags, category) = Self::unpack_deposit(&mut reader)?;
+                Self::Contribute {
+                    amount,
+                    flags,
+                    category,
+                }
+            }
+            discriminator::WITHDRAW_WITH_REBATE => Self::WithdrawWithRebate {
+                amount: reader.u64()?,
+                bump: reader.u8()?,
+            },
+            discriminator::SET_REBATE => Self::SetRebate {
+                lamports: reader.u64()?,
+            },
+            discriminator::SWEEP_DUST => Self::SweepDust,
+            discriminator::SET_ALLOWED_CALLERS => {
+                let count = reader.u8()?;
+                if count as usize > Vault::MAX_ALLOWED_CALLERS {
                     return Err(ProgramError::InvalidInstructionData);
                 }
-                Self::Initialize { bump: data[1] }
+                let mut callers = [[0u8; 32]; Vault::MAX_ALLOWED_CALLERS];
+                for caller in callers.iter_mut().take(count as usize) {
+                    caller.copy_from_slice(reader.take(32)?);
+                }
+                Self::SetAllowedCallers { count, callers }
+            }
+            discriminator::VERIFY => Self::Verify,
+            discriminator::WITHDRAW_PERCENT_OF_ACTUAL => {
+                let bps = reader.u16()?;
+                if bps == 0 || bps > withdraw_percent_of_actual::MAX_BPS {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                Self::WithdrawPercentOfActual { bps }
+            }
+            discriminator::SET_DEPOSIT_DELAY => Self::SetDepositDelay {
+                slots: reader.u64()?,
+            },
+            discriminator::INITIALIZE_AUTO_BUMP => Self::InitializeAutoBump,
+            discriminator::SET_OPTIONS => {
+                let options = reader.u8()?;
+                if options & !set_options::OPTIONS_MASK != 0 {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                Self::SetOptions { options }
+            }
+            discriminator::WITHDRAW_SPLIT => {
+                let amount = reader.u64()?;
+                let bump = reader.u8()?;
+                let count = reader.u8()?;
+                if count == 0 || count as usize > withdraw_split::MAX_SPLIT_RECIPIENTS {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                let mut shares = [0u16; withdraw_split::MAX_SPLIT_RECIPIENTS];
+                for share in shares.iter_mut().take(count as usize) {
+                    *share = reader.u16()?;
+                }
+                let total: u32 = shares.iter().map(|&bps| bps as u32).sum();
+                if total != withdraw_percent_of_actual::MAX_BPS as u32 {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                Self::WithdrawSplit {
+                    amount,
+                    bump,
+                    count,
+                    shares,
+                }
+            }
+            discriminator::ALLOCATE_VAULT => Self::AllocateVault { bump: reader.u8()? },
+            discriminator::INITIALIZE_ALLOCATED => Self::InitializeAllocated { bump: reader.u8()? },
+            discriminator::SET_UNLOCK_SLOT => Self::SetUnlockSlot {
+                slot: reader.u64()?,
+            },
+            discriminator::PING => Self::Ping,
+            discriminator::ATTEST => Self::Attest,
+            discriminator::QUERY_BATCH => Self::QueryBatch,
+            discriminator::INITIALIZE_CONFIG => Self::InitializeConfig {
+                admin: reader.take(32)?.try_into().unwrap(),
+                treasury: reader.take(32)?.try_into().unwrap(),
+                creation_fee: reader.u64()?,
+            },
+            discriminator::SET_PAUSED => Self::SetPaused {
+                paused: match reader.u8()? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(ProgramError::InvalidInstructionData),
+                },
+            },
+            discriminator::SET_AUTHORITY => Self::SetAuthority {
+                new_authority: reader.take(32)?.try_into().unwrap(),
+            },
+            discriminator::BATCH_DEPOSIT => {
+                let count = reader.u8()?;
+                if count == 0 || count as usize > batch_deposit::MAX_BATCH_DEPOSITS {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                let mut amounts = [0u64; batch_deposit::MAX_BATCH_DEPOSITS];
+                for amount in amounts.iter_mut().take(count as usize) {
+                    *amount = reader.u64()?;
+                }
+                Self::BatchDeposit { count, amounts }
             }
-            1 => {
-                if data.len() < 9 {
+            discriminator::SET_DEPOSIT_FEE => {
+                let fee_bps = reader.u16()?;
+                if fee_bps > withdraw_percent_of_actual::MAX_BPS {
                     return Err(ProgramError::InvalidInstructionData);
                 }
-                let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
-                Self::Deposit { amount }
+                Self::SetDepositFee { fee_bps }
             }
-            2 => {
-                if data.len() < 10 {
+            discriminator::REFUND => Self::Refund {
+                amount: reader.u64()?,
+                bump: reader.u8()?,
+            },
+            discriminator::REOPEN => Self::Reopen {
+                bump: reader.u8()?,
+                label: reader.optional_label()?,
+            },
+            discriminator::REAP_TOMBSTONE => Self::ReapTombstone,
+            discriminator::SET_COSIGNER => Self::SetCosigner {
+                single_sig_limit: reader.u64()?,
+                cosigner: reader.take(32)?.try_into().unwrap(),
+            },
+            discriminator::ADMIN_RECONCILE => Self::AdminReconcile,
+            discriminator::RELABEL_VAULT => Self::RelabelVault {
+                new_label: reader.take(VAULT_LABEL_LEN)?.try_into().unwrap(),
+                new_bump: reader.u8()?,
+            },
+            #[cfg(feature = "test-hooks")]
+            discriminator::SET_STATE_RAW => {
+                let offset = reader.u16()?;
+                let len = reader.u8()?;
+                if len as usize > set_state_raw::MAX_RAW_WRITE {
                     return Err(ProgramError::InvalidInstructionData);
                 }
-                let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
-                let bump = data[9];
-                Self::Withdraw { amount, bump }
+                let mut bytes = [0u8; set_state_raw::MAX_RAW_WRITE];
+                bytes[..len as usize].copy_from_slice(reader.take(len as usize)?);
+                Self::SetStateRaw { offset, len, bytes }
             }
             _ => return Err(ProgramError::InvalidInstructionData),
-        })
+        };
+
+        reader.finish()?;
+        Ok(instruction)
+    }
+
+    /// Parse the fields shared by `Deposit` and `Contribute`
+    fn unpack_deposit(reader: &mut Reader) -> Result<(u64, u8, Option<u8>), ProgramError> {
+        let amount = reader.u64()?;
+
+        let flags = reader.optional_u8().unwrap_or(0);
+        if flags & !deposit::FLAGS_MASK != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let category = reader.optional_u8();
+
+        Ok((amount, flags, category))
     }
 
+    /// Route to the instruction's handler
+    ///
+    /// Handlers destructure `accounts` into the shape they need themselves;
+    /// instructions that take no accounts are routed without it.
     pub fn process(&self, program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
         match self {
-            Self::Initialize { bump } => initialize::handler(program_id, accounts, *bump),
-            Self::Deposit { amount } => deposit::handler(program_id, accounts, *amount),
-            Self::Withdraw { amount, bump } => {
-                withdraw::handler(program_id, accounts, *amount, *bump)
+            Self::Initialize {
+                bump,
+                label,
+                max_balance,
+                vault_type,
+                party,
+                min_deposit,
+            } => initialize::handler(
+                program_id,
+                accounts,
+                *bump,
+                label,
+                *max_balance,
+                *vault_type,
+                party,
+                *min_deposit,
+            ),
+            Self::Deposit {
+                amount,
+                flags,
+                category,
+            } => deposit::handler(program_id, accounts, *amount, *flags, *category),
+            Self::Withdraw {
+                amount,
+                bump,
+                label,
+            } => withdraw::handler(program_id, accounts, *amount, *bump, label),
+            Self::CloseVault {
+                bump,
+                force,
+                grace_slots,
+            } => close::handler(program_id, accounts, *bump, *force, *grace_slots),
+            Self::TransferOwnership { new_owner } => {
+                transfer_ownership::handler(program_id, accounts, new_owner)
+            }
+            Self::WithdrawAll { bump } => withdraw_all::handler(program_id, accounts, *bump),
+            Self::InitializeIfNeeded { bump } => {
+                initialize_if_needed::handler(program_id, accounts, *bump)
+            }
+            Self::WithdrawTo { amount, bump } => {
+                withdraw_to::handler(program_id, accounts, *amount, *bump)
+            }
+            Self::Contribute {
+                amount,
+                flags,
+                category,
+            } => contribute::handler(program_id, accounts, *amount, *flags, *category),
+            Self::WithdrawWithRebate { amount, bump } => {
+                withdraw_with_rebate::handler(program_id, accounts, *amount, *bump)
+            }
+            Self::SetRebate { lamports } => set_rebate::handler(program_id, accounts, *lamports),
+            Self::SweepDust => sweep_dust::handler(program_id, accounts),
+            Self::SetAllowedCallers { count, callers } => {
+                set_allowed_callers::handler(program_id, accounts, &callers[..*count as usize])
+            }
+            Self::Verify => verify::handler(program_id, accounts),
+            Self::WithdrawPercentOfActual { bps } => {
+                withdraw_percent_of_actual::handler(program_id, accounts, *bps)
+            }
+            Self::SetDepositDelay { slots } => {
+                set_deposit_delay::handler(program_id, accounts, *slots)
+            }
+            Self::InitializeAutoBump => initialize_auto_bump::handler(program_id, accounts),
+            Self::SetOptions { options } => set_options::handler(program_id, accounts, *options),
+            Self::WithdrawSplit {
+                amount,
+                bump,
+                count,
+                shares,
+            } => withdraw_split::handler(
+                program_id,
+                accounts,
+                *amount,
+                *bump,
+                &shares[..*count as usize],
+            ),
+            Self::AllocateVault { bump } => allocate_vault::handler(program_id, accounts, *bump),
+            Self::InitializeAllocated { bump } => {
+                initialize_allocated::handler(program_id, accounts, *bump)
+            }
+            Self::SetUnlockSlot { slot } => set_unlock_slot::handler(program_id, accounts, *slot),
+            Self::Ping => ping::handler(),
+            Self::Attest => attest::handler(program_id, accounts),
+            Self::QueryBatch => query_batch::handler(program_id, accounts),
+            Self::InitializeConfig {
+                admin,
+                treasury,
+                creation_fee,
+            } => initialize_config::handler(program_id, accounts, admin, treasury, *creation_fee),
+            Self::SetPaused { paused } => set_paused::handler(program_id, accounts, *paused),
+            Self::SetAuthority { new_authority } => {
+                set_authority::handler(program_id, accounts, new_authority)
+            }
+            Self::BatchDeposit { count, amounts } => {
+                batch_deposit::handler(program_id, accounts, &amounts[..*count as usize])
+            }
+            Self::SetDepositFee { fee_bps } => {
+                set_deposit_fee::handler(program_id, accounts, *fee_bps)
+            }
+            Self::Refund { amount, bump } => refund::handler(program_id, accounts, *amount, *bump),
+            Self::Reopen { bump, label } => reopen::handler(program_id, accounts, *bump, label),
+            Self::ReapTombstone => reap_tombstone::handler(program_id, accounts),
+            Self::SetCosigner {
+                single_sig_limit,
+                cosigner,
+            } => set_cosigner::handler(program_id, accounts, *single_sig_limit, cosigner),
+            Self::AdminReconcile => admin_reconcile::handler(program_id, accounts),
+            Self::RelabelVault {
+                new_label,
+                new_bump,
+            } => relabel_vault::handler(program_id, accounts, new_label, *new_bump),
+            #[cfg(feature = "test-hooks")]
+            Self::SetStateRaw { offset, len, bytes } => {
+                set_state_raw::handler(program_id, accounts, *offset, &bytes[..*len as usize])
+            }
+        }
+    }
+}
+
+/// Cursor over instruction data that consumes fields front to back
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    /// Consume `len` required bytes
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ProgramError> {
+        if self.0.len() < len {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    /// Consume a required u8
+    fn u8(&mut self) -> Result<u8, ProgramError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Consume a required little-endian u16
+    fn u16(&mut self) -> Result<u16, ProgramError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    /// Consume a required little-endian u64
+    fn u64(&mut self) -> Result<u64, ProgramError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Consume an optional trailing u8, `None` if the data ends here
+    fn optional_u8(&mut self) -> Option<u8> {
+        self.u8().ok()
+    }
+
+    /// Consume an optional trailing u64, 0 if the data ends here
+    fn optional_u64(&mut self) -> Result<u64, ProgramError> {
+        if self.0.is_empty() {
+            return Ok(0);
+        }
+        self.u64()
+    }
+
+    /// Consume an optional trailing vault label, the default label if the
+    /// data ends here
+    fn optional_label(&mut self) -> Result<[u8; VAULT_LABEL_LEN], ProgramError> {
+        if self.0.is_empty() {
+            return Ok(DEFAULT_VAULT_LABEL);
+        }
+        Ok(self.take(VAULT_LABEL_LEN)?.try_into().unwrap())
+    }
+
+    /// Fail if any bytes remain, so a malformed payload is not half-read
+    fn finish(self) -> Result<(), ProgramError> {
+        if !self.0.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Concatenate a discriminator and its fields into instruction data
+    fn ix(discriminator: u8, fields: &[&[u8]]) -> Vec<u8> {
+        let mut data = vec![discriminator];
+        for field in fields {
+            data.extend_from_slice(field);
+        }
+        data
+    }
+
+    fn rejects(data: &[u8]) -> bool {
+        VaultInstruction::unpack(data) == Err(ProgramError::InvalidInstructionData)
+    }
+
+    #[test]
+    fn empty_data_is_rejected() {
+        assert!(rejects(&[]));
+    }
+
+    #[test]
+    fn unknown_discriminator_is_rejected() {
+        assert!(rejects(&[36]));
+        assert!(rejects(&[0xFE, 0, 0, 0]));
+        #[cfg(not(feature = "test-hooks"))]
+        assert!(rejects(&[0xFF, 0, 0, 0]));
+    }
+
+    #[test]
+    fn every_instruction_parses_its_core_fields() {
+        let amount = 500u64.to_le_bytes();
+        let key = [7u8; 32];
+        let cases = [
+            (
+                ix(0, &[&[254]]),
+                VaultInstruction::Initialize {
+                    bump: 254,
+                    label: DEFAULT_VAULT_LABEL,
+                    max_balance: 0,
+                    vault_type: VaultType::Standard,
+                    party: [0; 32],
+                    min_deposit: 0,
+                },
+            ),
+            (
+                ix(1, &[&amount]),
+                VaultInstruction::Deposit {
+                    amount: 500,
+                    flags: 0,
+                    category: None,
+                },
+            ),
+            (
+                ix(2, &[&amount, &[9]]),
+                VaultInstruction::Withdraw {
+                    amount: 500,
+                    bump: 9,
+                    label: DEFAULT_VAULT_LABEL,
+                },
+            ),
+            (
+                ix(3, &[&[9]]),
+                VaultInstruction::CloseVault {
+                    bump: 9,
+                    force: false,
+                    grace_slots: 0,
+                },
+            ),
+            (
+                ix(4, &[&key]),
+                VaultInstruction::TransferOwnership { new_owner: key },
+            ),
+            (ix(5, &[&[9]]), VaultInstruction::WithdrawAll { bump: 9 }),
+            (
+                ix(6, &[&[9]]),
+                VaultInstruction::InitializeIfNeeded { bump: 9 },
+            ),
+            (
+                ix(7, &[&amount, &[9]]),
+                VaultInstruction::WithdrawTo {
+                    amount: 500,
+                    bump: 9,
+                },
+            ),
+            (
+                ix(8, &[&amount]),
+                VaultInstruction::Contribute {
+                    amount: 500,
+                    flags: 0,
+                    category: None,
+                },
+            ),
+            (
+                ix(9, &[&amount, &[9]]),
+                VaultInstruction::WithdrawWithRebate {
+                    amount: 500,
+                    bump: 9,
+                },
+            ),
+            (
+                ix(10, &[&amount]),
+                VaultInstruction::SetRebate { lamports: 500 },
+            ),
+            (ix(11, &[]), VaultInstruction::SweepDust),
+            (
+                ix(12, &[&[1], &key]),
+                VaultInstruction::SetAllowedCallers {
+                    count: 1,
+                    callers: [key, [0; 32], [0; 32], [0; 32]],
+                },
+            ),
+            (ix(13, &[]), VaultInstruction::Verify),
+            (
+                ix(14, &[&2_500u16.to_le_bytes()]),
+                VaultInstruction::WithdrawPercentOfActual { bps: 2_500 },
+            ),
+            (
+                ix(15, &[&amount]),
+                VaultInstruction::SetDepositDelay { slots: 500 },
+            ),
+            (ix(16, &[]), VaultInstruction::InitializeAutoBump),
+            (
+                ix(17, &[&[set_options::OPTIONS_MASK]]),
+                VaultInstruction::SetOptions {
+                    options: set_options::OPTIONS_MASK,
+                },
+            ),
+            (
+                ix(
+                    18,
+                    &[
+                        &amount,
+                        &[9, 2],
+                        &4_000u16.to_le_bytes(),
+                        &6_000u16.to_le_bytes(),
+                    ],
+                ),
+                VaultInstruction::WithdrawSplit {
+                    amount: 500,
+                    bump: 9,
+                    count: 2,
+                    shares: [4_000, 6_000, 0, 0],
+                },
+            ),
+            (ix(19, &[&[9]]), VaultInstruction::AllocateVault { bump: 9 }),
+            (
+                ix(20, &[&[9]]),
+                VaultInstruction::InitializeAllocated { bump: 9 },
+            ),
+            (
+                ix(21, &[&amount]),
+                VaultInstruction::SetUnlockSlot { slot: 500 },
+            ),
+            (ix(22, &[]), VaultInstruction::Ping),
+            (ix(23, &[]), VaultInstruction::Attest),
+            (ix(24, &[]), VaultInstruction::QueryBatch),
+            (
+                ix(25, &[&key, &[8; 32], &amount]),
+                VaultInstruction::InitializeConfig {
+                    admin: key,
+                    treasury: [8; 32],
+                    creation_fee: 500,
+                },
+            ),
+            (
+                ix(26, &[&[1]]),
+                VaultInstruction::SetPaused { paused: true },
+            ),
+            (
+                ix(27, &[&key]),
+                VaultInstruction::SetAuthority { new_authority: key },
+            ),
+            (
+                ix(28, &[&[1], &amount]),
+                VaultInstruction::BatchDeposit {
+                    count: 1,
+                    amounts: [500, 0, 0, 0, 0, 0, 0, 0],
+                },
+            ),
+            (
+                ix(29, &[&250u16.to_le_bytes()]),
+                VaultInstruction::SetDepositFee { fee_bps: 250 },
+            ),
+            (
+                ix(30, &[&amount, &[9]]),
+                VaultInstruction::Refund {
+                    amount: 500,
+                    bump: 9,
+                },
+            ),
+            (
+                ix(31, &[&[9]]),
+                VaultInstruction::Reopen {
+                    bump: 9,
+                    label: DEFAULT_VAULT_LABEL,
+                },
+            ),
+            (ix(32, &[]), VaultInstruction::ReapTombstone),
+            (
+                ix(33, &[&amount, &key]),
+                VaultInstruction::SetCosigner {
+                    single_sig_limit: 500,
+                    cosigner: key,
+                },
+            ),
+            (ix(34, &[]), VaultInstruction::AdminReconcile),
+            (
+                ix(35, &[b"checking\0\0\0\0\0\0\0\0", &[9]]),
+                VaultInstruction::RelabelVault {
+                    new_label: *b"checking\0\0\0\0\0\0\0\0",
+                    new_bump: 9,
+                },
+            ),
+        ];
+
+        for (data, expected) in cases {
+            assert_eq!(VaultInstruction::unpack(&data), Ok(expected));
+            if data.len() > 1 {
+                assert!(
+                    rejects(&data[..data.len() - 1]),
+                    "short data for discriminator {}",
+                    data[0]
+                );
             }
         }
     }
+
+    #[test]
+    fn optional_fields_are_parsed_when_present() {
+        let label = *b"savings\0\0\0\0\0\0\0\0\0";
+        let party = [3u8; 32];
+        assert_eq!(
+            VaultInstruction::unpack(&ix(
+                0,
+                &[
+                    &[254],
+                    &label,
+                    &1_000u64.to_le_bytes(),
+                    &[VaultType::Escrow as u8],
+                    &party,
+                    &10u64.to_le_bytes(),
+                ],
+            )),
+            Ok(VaultInstruction::Initialize {
+                bump: 254,
+                label,
+                max_balance: 1_000,
+                vault_type: VaultType::Escrow,
+                party,
+                min_deposit: 10,
+            })
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&ix(
+                1,
+                &[
+                    &42u64.to_le_bytes(),
+                    &[deposit::FLAG_PRESERVE_PAYER_RENT, 5]
+                ],
+            )),
+            Ok(VaultInstruction::Deposit {
+                amount: 42,
+                flags: deposit::FLAG_PRESERVE_PAYER_RENT,
+                category: Some(5),
+            })
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&ix(3, &[&[9, 1], &50u64.to_le_bytes()])),
+            Ok(VaultInstruction::CloseVault {
+                bump: 9,
+                force: true,
+                grace_slots: 50,
+            })
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&ix(31, &[&[9], &label])),
+            Ok(VaultInstruction::Reopen { bump: 9, label })
+        );
+    }
+
+    #[test]
+    fn invalid_field_values_are_rejected() {
+        let amount = 500u64.to_le_bytes();
+
+        // Initialize: unknown vault type, missing party
+        assert!(rejects(&ix(
+            0,
+            &[&[254], &DEFAULT_VAULT_LABEL, &[0; 8], &[4]]
+        )));
+        assert!(rejects(&ix(
+            0,
+            &[&[254], &DEFAULT_VAULT_LABEL, &[0; 8], &[3]]
+        )));
+        // Deposit and Contribute: unknown flag bits
+        assert!(rejects(&ix(1, &[&amount, &[0x80]])));
+        assert!(rejects(&ix(8, &[&amount, &[0x80]])));
+        // CloseVault: force is a bool
+        assert!(rejects(&ix(3, &[&[9, 2]])));
+        // SetAllowedCallers: over the maximum
+        assert!(rejects(&ix(12, &[&[Vault::MAX_ALLOWED_CALLERS as u8 + 1]])));
+        // WithdrawPercentOfActual: zero or over 100%
+        assert!(rejects(&ix(14, &[&[0, 0]])));
+        assert!(rejects(&ix(14, &[&10_001u16.to_le_bytes()])));
+        // SetOptions: unknown bits
+        assert!(rejects(&ix(17, &[&[!set_options::OPTIONS_MASK]])));
+        // WithdrawSplit: no recipients, too many, shares not summing to 100%
+        assert!(rejects(&ix(18, &[&amount, &[9, 0]])));
+        assert!(rejects(&ix(
+            18,
+            &[
+                &amount,
+                &[9, withdraw_split::MAX_SPLIT_RECIPIENTS as u8 + 1]
+            ],
+        )));
+        assert!(rejects(&ix(
+            18,
+            &[&amount, &[9, 1], &9_999u16.to_le_bytes()]
+        )));
+        // SetPaused: paused is a bool
+        assert!(rejects(&ix(26, &[&[2]])));
+        // BatchDeposit: no amounts, too many
+        assert!(rejects(&ix(28, &[&[0]])));
+        assert!(rejects(&ix(
+            28,
+            &[&[batch_deposit::MAX_BATCH_DEPOSITS as u8 + 1]],
+        )));
+        // SetDepositFee: over 100%
+        assert!(rejects(&ix(29, &[&10_001u16.to_le_bytes()])));
+    }
+
+    #[test]
+    fn optional_fields_may_stop_at_any_field() {
+        let label = *b"trading\0\0\0\0\0\0\0\0\0";
+        let party = [3u8; 32];
+        let amount = 42u64.to_le_bytes();
+        let initialize = |label, max_balance, vault_type, party| VaultInstruction::Initialize {
+            bump: 254,
+            label,
+            max_balance,
+            vault_type,
+            party,
+            min_deposit: 0,
+        };
+
+        assert_eq!(
+            VaultInstruction::unpack(&ix(0, &[&[254], &label])),
+            Ok(initialize(label, 0, VaultType::Standard, [0; 32]))
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&ix(0, &[&[254], &label, &5_000u64.to_le_bytes()])),
+            Ok(initialize(label, 5_000, VaultType::Standard, [0; 32]))
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&ix(
+                0,
+                &[&[254], &label, &[0; 8], &[VaultType::Savings as u8]]
+            )),
+            Ok(initialize(label, 0, VaultType::Savings, [0; 32]))
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&ix(
+                0,
+                &[&[254], &label, &[0; 8], &[VaultType::Escrow as u8], &party]
+            )),
+            Ok(initialize(label, 0, VaultType::Escrow, party))
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&ix(1, &[&amount, &[deposit::FLAG_PRESERVE_PAYER_RENT]])),
+            Ok(VaultInstruction::Deposit {
+                amount: 42,
+                flags: deposit::FLAG_PRESERVE_PAYER_RENT,
+                category: None,
+            })
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&ix(8, &[&amount, &[0, 3]])),
+            Ok(VaultInstruction::Contribute {
+                amount: 42,
+                flags: 0,
+                category: Some(3),
+            })
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&ix(2, &[&amount, &[9], &label])),
+            Ok(VaultInstruction::Withdraw {
+                amount: 42,
+                bump: 9,
+                label,
+            })
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&ix(3, &[&[9, 1]])),
+            Ok(VaultInstruction::CloseVault {
+                bump: 9,
+                force: true,
+                grace_slots: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn partial_optional_fields_are_rejected() {
+        let amount = 42u64.to_le_bytes();
+        let label = &DEFAULT_VAULT_LABEL;
+
+        // Initialize: partial label, max balance, party or min deposit
+        assert!(rejects(&ix(0, &[&[254], b"tr"])));
+        assert!(rejects(&ix(0, &[&[254], label, &[0; 7]])));
+        assert!(rejects(&ix(0, &[&[254], label, &[0; 8], &[2], &[3; 31]])));
+        assert!(rejects(&ix(
+            0,
+            &[&[254], label, &[0; 8], &[2], &[3; 32], &[0; 7]]
+        )));
+        // Withdraw and Reopen: partial label
+        assert!(rejects(&ix(2, &[&amount, &[9], b"tr"])));
+        assert!(rejects(&ix(31, &[&[9], b"tr"])));
+        // CloseVault: partial grace period
+        assert!(rejects(&ix(3, &[&[9, 0], &[0; 7]])));
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        let amount = 500u64.to_le_bytes();
+        let key = [7u8; 32];
+        let label = &DEFAULT_VAULT_LABEL;
+
+        // Instructions with optional fields, each with all of them present
+        let with_optionals = [
+            ix(0, &[&[254], label, &[0; 8], &[2], &key, &[0; 8]]),
+            ix(1, &[&amount, &[0, 7]]),
+            ix(2, &[&amount, &[9], label]),
+            ix(3, &[&[9, 1], &[0; 8]]),
+            ix(8, &[&amount, &[0, 7]]),
+            ix(31, &[&[9], label]),
+        ];
+        // Instructions whose data has a fixed length
+        let fixed = [
+            ix(4, &[&key]),
+            ix(5, &[&[9]]),
+            ix(6, &[&[9]]),
+            ix(7, &[&amount, &[9]]),
+            ix(9, &[&amount, &[9]]),
+            ix(10, &[&amount]),
+            ix(11, &[]),
+            ix(13, &[]),
+            ix(14, &[&[1, 0]]),
+            ix(15, &[&amount]),
+            ix(16, &[]),
+            ix(17, &[&[0]]),
+            ix(19, &[&[9]]),
+            ix(20, &[&[9]]),
+            ix(21, &[&amount]),
+            ix(22, &[]),
+            ix(23, &[]),
+            ix(24, &[]),
+            ix(25, &[&key, &key, &amount]),
+            ix(26, &[&[1]]),
+            ix(27, &[&key]),
+            ix(29, &[&[0, 0]]),
+            ix(30, &[&amount, &[9]]),
+            ix(32, &[]),
+            ix(33, &[&amount, &key]),
+            ix(34, &[]),
+            ix(35, &[label, &[9]]),
+        ];
+
+        for data in with_optionals.iter().chain(&fixed) {
+            assert!(
+                VaultInstruction::unpack(data).is_ok(),
+                "full data for discriminator {}",
+                data[0]
+            );
+            let mut long = data.clone();
+            long.push(0);
+            assert!(
+                rejects(&long),
+                "trailing byte for discriminator {}",
+                data[0]
+            );
+        }
+    }
+
+    #[test]
+    fn over_long_payloads_are_rejected() {
+        let amount = 500u64.to_le_bytes();
+        let key = [7u8; 32];
+        let share = 10_000u16.to_le_bytes();
+
+        // More entries than the count announces
+        assert!(rejects(&ix(12, &[&[1], &key, &key])));
+        assert!(rejects(&ix(18, &[&amount, &[9, 1], &share, &[0, 0]])));
+        assert!(rejects(&ix(28, &[&[1], &amount, &amount])));
+
+        // A count over the maximum, even with every entry present
+        let callers = Vault::MAX_ALLOWED_CALLERS + 1;
+        assert!(rejects(&ix(
+            12,
+            &[&[callers as u8], &vec![key; callers].concat()]
+        )));
+        let recipients = withdraw_split::MAX_SPLIT_RECIPIENTS + 1;
+        assert!(rejects(&ix(
+            18,
+            &[
+                &amount,
+                &[9, recipients as u8],
+                &share,
+                &vec![0; 2 * (recipients - 1)]
+            ],
+        )));
+        let deposits = batch_deposit::MAX_BATCH_DEPOSITS + 1;
+        assert!(rejects(&ix(
+            28,
+            &[&[deposits as u8], &vec![amount; deposits].concat()]
+        )));
+    }
+
+    #[cfg(feature = "test-hooks")]
+    #[test]
+    fn set_state_raw_is_bounded() {
+        let mut bytes = [0u8; set_state_raw::MAX_RAW_WRITE];
+        bytes[..2].copy_from_slice(&[1, 2]);
+        assert_eq!(
+            VaultInstruction::unpack(&ix(0xFF, &[&48u16.to_le_bytes(), &[2, 1, 2]])),
+            Ok(VaultInstruction::SetStateRaw {
+                offset: 48,
+                len: 2,
+                bytes,
+            })
+        );
+        assert!(rejects(&ix(0xFF, &[&48u16.to_le_bytes(), &[2, 1]])));
+        assert!(rejects(&ix(0xFF, &[&48u16.to_le_bytes(), &[2, 1, 2, 3]])));
+        assert!(rejects(&ix(
+            0xFF,
+            &[
+                &0u16.to_le_bytes(),
+                &[set_state_raw::MAX_RAW_WRITE as u8 + 1]
+            ],
+        )));
+    }
 }
@@ -0,0 +1,18 @@
+use pinocchio::cpi::set_return_data;
+use pinocchio::ProgramResult;
+
+use crate::state::vault::PROGRAM_VERSION;
+
+/// Process ping instruction
+///
+/// Liveness and version probe that touches no accounts. Routed without an
+/// account slice at all, so it succeeds with an empty account list.
+///
+/// Return data: [program version: u32]
+///
+/// Accounts: none
+pub fn handler() -> ProgramResult {
+    set_return_data(&PROGRAM_VERSION.to_le_bytes());
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use pinocchio::cpi::set_return_data;
+use pinocchio::error::ProgramError;
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::state::vault::{Vault, AMOUNT_LEN};
+
+/// Most vaults one `QueryBatch` can report
+///
+/// Keeps the return data well under the runtime's 1024 byte limit in both
+/// the default and `wide-amount` layouts.
+pub const MAX_QUERY_BATCH: usize = 16;
+
+/// Size of one vault's entry in the `QueryBatch` return data
+pub const QUERY_BATCH_ENTRY_LEN: usize = 32 + AMOUNT_LEN;
+
+/// Process query-batch instruction
+///
+/// Reports the owner and tracked amount of every passed vault in one
+/// return-data blob, so a dashboard can read many vaults with a single
+/// simulated transaction. Fails as a whole if any account is not a vault.
+/// Read only; intended to be simulated.
+///
+/// Return data: [owner: [u8; 32], amount: VaultAmount] per vault, in
+/// account order
+///
+/// Accounts:
+/// 0..N. `[]` vault PDA accounts, 1 <= N <= `MAX_QUERY_BATCH`
+pub fn handler(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    if accounts.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    if accounts.len() > MAX_QUERY_BATCH {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut data = [0u8; MAX_QUERY_BATCH * QUERY_BATCH_ENTRY_LEN];
+    for (vault, entry) in accounts
+        .iter()
+        .zip(data.chunks_exact_mut(QUERY_BATCH_ENTRY_LEN))
+    {
+        if !vault.owned_by(program_id) {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        // SAFETY: no active borrows of vault data at this point
+        let vault_state = unsafe { Vault::from_account(vault) }?;
+        entry[..32].copy_from_slice(&vault_state.owner_bytes());
+        entry[32..].copy_from_slice(&vault_state.amount().to_le_bytes());
+    }
+    set_return_data(&data[..accounts.len() * QUERY_BATCH_ENTRY_LEN]);
+
+    Ok(())
+}
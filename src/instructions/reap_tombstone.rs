@@ -0,0 +1,51 @@
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::error::VaultError;
+use crate::state::tombstone::Tombstone;
+use crate::state::vault::CLOSED_DISCRIMINATOR;
+
+/// Process reap-tombstone instruction
+///
+/// Finishes a close that kept a tombstone: the tombstone's rent goes to the
+/// owner recorded in it and the runtime then removes the account, after
+/// which the vault can no longer be reopened. The owner may reap at any
+/// time, giving up the rest of the grace period.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner
+/// 1. `[writable]` tombstone (the vault PDA account)
+pub fn handler(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [owner, vault, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !owner.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    if unsafe { Tombstone::from_account(vault) }?.owner() != owner.address() {
+        return Err(VaultError::OwnerMismatch.into());
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    let data = unsafe { vault.borrow_unchecked_mut() };
+    data.fill(0);
+    data[Tombstone::DISCRIMINATOR_OFFSET..Tombstone::OWNER_OFFSET]
+        .copy_from_slice(&CLOSED_DISCRIMINATOR);
+
+    let lamports = vault.lamports();
+    owner.set_lamports(
+        owner
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+    );
+    vault.set_lamports(0);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::error::VaultError;
+use crate::instructions::{contribute, withdraw};
+use crate::state::receipt::Receipt;
+use crate::utils::events;
+use crate::utils::helpers::system_program_check;
+
+/// Process refund instruction
+///
+/// Returns `amount` to a contributor, for example when an escrow deal falls
+/// through. The owner signs, but the funds can only go back to a
+/// contributor other than the owner, and no more than their receipt for the
+/// vault still records. Each contributor has their own receipt, so a later
+/// contribution from someone else cannot take away an earlier one's refund.
+///
+/// A refund is a withdraw-type instruction like any other: the unlock slot
+/// and the vault type's withdraw rules still apply.
+///
+/// Accounts:
+/// 0. `[signer]` owner
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program
+/// 3. `[writable]` contributor to refund
+/// 4. `[writable]` contributor's receipt PDA `[b"receipt", vault, contributor]`
+/// 5. `[]` instructions sysvar (optional, required for allow-listed CPI withdraws
+///    and single-op-per-transaction and no-flash vaults)
+/// 6. `[signer]` escrow counterparty (optional, after the sysvar if passed,
+///    required for escrow vaults)
+/// 7. `[signer]` cosigner (optional, after the sysvar if passed, required
+///    above the single signature limit)
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    amount: u64,
+    bump: u8,
+) -> ProgramResult {
+    let [owner, vault, system_program, depositor, receipt, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    withdraw::validate(program_id, owner, vault)?;
+    withdraw::verify_address(program_id, vault, bump)?;
+    withdraw::verify_stored_address(program_id, vault)?;
+    withdraw::check_caller(vault, remaining.first())?;
+    withdraw::check_single_op(program_id, vault, remaining.first())?;
+    withdraw::check_no_flash(program_id, vault, remaining.first())?;
+    withdraw::check_unlocked(vault)?;
+    withdraw::check_vault_type(vault, remaining)?;
+
+    // An owner's own contribution is withdrawn, not refunded
+    if depositor.address() == owner.address() {
+        return Err(VaultError::DepositorMismatch.into());
+    }
+    let (refundable, bump) = contribute::checked_receipt(program_id, vault, depositor, receipt)
+        .map_err(|_| VaultError::DepositorMismatch)?;
+    let refundable = refundable
+        .checked_sub(amount)
+        .ok_or(VaultError::InsufficientBalance)?;
+
+    withdraw::check_cosigner(vault, amount, remaining)?;
+    withdraw::release(vault, depositor, amount, &Rent::get()?)?;
+    withdraw::count_withdrawal(vault)?;
+    // SAFETY: the read view in `checked_receipt` is no longer used
+    Receipt::write(unsafe { receipt.borrow_unchecked_mut() }, refundable, bump);
+
+    events::record(vault, "refund")?;
+
+    Ok(())
+}
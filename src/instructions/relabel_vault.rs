@@ -0,0 +1,102 @@
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{cpi::Signer, error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_system::create_account_with_minimum_balance_signed;
+
+use crate::instructions::withdraw;
+use crate::state::vault::{
+    create_vault_address, vault_signer_seeds, Vault, VaultMut, VAULT_LABEL_LEN,
+};
+use crate::utils::events;
+use crate::utils::helpers::{system_program_check, uninitialized_check, vault_backing};
+
+/// Process relabel-vault instruction
+///
+/// Moves a vault to the PDA of its seed owner and `new_label`, since the
+/// label is part of the seeds and cannot change in place. The new vault is
+/// created at `new_bump`, receives every lamport above the old vault's rent
+/// and a copy of its state with only the label and bump replaced, and the
+/// old vault is closed like `CloseVault`, its rent returned to the owner.
+/// Owner-signed, and atomic since it is one instruction.
+///
+/// Contribution receipts stay keyed by the old vault address, so `Refund`
+/// cannot draw on them after a relabel.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner / payer of the new vault's rent
+/// 1. `[writable]` vault PDA account
+/// 2. `[writable]` new vault PDA account `[b"vault", seed owner, new_label]`
+/// 3. `[]` system_program
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    new_label: &[u8; VAULT_LABEL_LEN],
+    new_bump: u8,
+) -> ProgramResult {
+    let [owner, vault, new_vault, system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    withdraw::validate(program_id, owner, vault)?;
+    withdraw::verify_stored_address(program_id, vault)?;
+
+    // Relabeling to the current label would target the vault itself
+    uninitialized_check(new_vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    let mut state = unsafe { Vault::from_account_unchecked(vault) }.to_bytes();
+    let seed_owner = *Vault::from_bytes(&state).seed_owner();
+    if create_vault_address(&seed_owner, new_label, new_bump, program_id).as_ref()
+        != Some(new_vault.address())
+    {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let mut new_state = VaultMut::from_bytes(&mut state);
+    new_state.set_label(new_label);
+    new_state.set_bump(new_bump);
+
+    // Create the new vault (PDA signed) and write the moved state
+    let bump_bytes = [new_bump];
+    let seeds = vault_signer_seeds(&seed_owner, new_label, &bump_bytes);
+    let signers = [Signer::from(seeds.as_slice())];
+    create_account_with_minimum_balance_signed(
+        new_vault,
+        Vault::LEN,
+        program_id,
+        owner,
+        None,
+        &signers,
+    )?;
+    // SAFETY: the account was just allocated, no active borrows
+    unsafe { new_vault.borrow_unchecked_mut() }.copy_from_slice(&state);
+
+    // Move everything above the old rent floor, tracked or not, and return
+    // the old rent to the owner
+    let moved = vault_backing(vault, &Rent::get()?)?;
+    let refund = vault
+        .lamports()
+        .checked_sub(moved)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    new_vault.set_lamports(
+        new_vault
+            .lamports()
+            .checked_add(moved)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+    );
+    owner.set_lamports(
+        owner
+            .lamports()
+            .checked_add(refund)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+    );
+    vault.set_lamports(0);
+
+    // SAFETY: no active borrows of vault data at this point
+    unsafe { VaultMut::from_account_unchecked(vault).mark_closed() };
+
+    events::record(new_vault, "relabel")?;
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_system::instructions::Transfer;
+
+use crate::error::VaultError;
+use crate::instructions::initialize;
+use crate::state::tombstone::Tombstone;
+use crate::state::vault::{Vault, VAULT_LABEL_LEN};
+use crate::utils::events;
+use crate::utils::helpers::{owner_account_check, system_program_check};
+
+/// Process reopen instruction
+///
+/// Restores a vault closed with a grace period: the tombstone grows back to
+/// a full vault account, its rent is topped up from the owner and the
+/// initial state is written again, so the vault starts empty with default
+/// settings. Only the owner recorded in the tombstone may reopen, and only
+/// while the current slot is before the close slot plus the grace period.
+/// The PDA must still derive from that owner, so a vault closed after a
+/// `TransferOwnership` cannot be reopened. `CloseVault` freed the vault's
+/// slot in the owner's vault counter, so it is counted again here.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner / payer
+/// 1. `[writable]` tombstone (the vault PDA account)
+/// 2. `[]` system_program
+/// 3. `[writable]` owner's vault counter PDA `[b"vault_counter", owner]`
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    bump: u8,
+    label: &[u8; VAULT_LABEL_LEN],
+) -> ProgramResult {
+    let [owner, vault, system_program, counter] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    // Validate owner is a signable account and signed
+    owner_account_check(owner)?;
+    if !owner.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    let tombstone = unsafe { Tombstone::from_account(vault) }?;
+    if tombstone.owner() != owner.address() {
+        return Err(VaultError::OwnerMismatch.into());
+    }
+    let deadline = tombstone
+        .close_slot()
+        .saturating_add(tombstone.grace_slots());
+    if Clock::get()?.slot >= deadline {
+        return Err(VaultError::GracePeriodExpired.into());
+    }
+
+    initialize::verify_bump(program_id, owner, vault, label, bump)?;
+    initialize::count_vault(program_id, owner, counter)?;
+
+    // Grow back to a full vault and top its rent up from the owner
+    vault.resize(Vault::LEN)?;
+    let minimum = Rent::get()?.try_minimum_balance(Vault::LEN)?;
+    let top_up = minimum.saturating_sub(vault.lamports());
+    if top_up > 0 {
+        Transfer {
+            from: owner,
+            to: vault,
+            lamports: top_up,
+        }
+        .invoke()?;
+    }
+
+    initialize::write_initial_state(owner, vault, label, bump)?;
+
+    events::record(vault, "reopen")?;
+
+    Ok(())
+}
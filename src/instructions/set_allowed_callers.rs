@@ -0,0 +1,40 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultMut};
+use crate::utils::events;
+
+/// Process set-allowed-callers instruction
+///
+/// Replaces the list of programs allowed to withdraw from the vault via CPI.
+/// An empty list leaves CPI withdraws unrestricted. Setting the current list
+/// succeeds without writing or logging an event.
+///
+/// Accounts:
+/// 0. `[signer]` authority
+/// 1. `[writable]` vault PDA account
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    callers: &[[u8; 32]],
+) -> ProgramResult {
+    let [authority, vault, ..] = accounts else {
+        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate_authority(program_id, authority, vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    let current = vault_state.allowed_callers();
+    if current.len() == callers.len() && current.iter().zip(callers).all(|(a, b)| a.as_ref() == b) {
+        return Ok(());
+    }
+
+    // SAFETY: the read view above is no longer used
+    unsafe { VaultMut::from_account_unchecked(vault).set_allowed_callers(callers) };
+
+    events::record(vault, "set_allowed_callers")?;
+
+    Ok(())
+}
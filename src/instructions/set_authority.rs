@@ -0,0 +1,44 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultMut};
+use crate::utils::events;
+
+/// Process set-authority instruction
+///
+/// Hands control of the vault's settings to `new_authority`. The owner and
+/// the funds are unaffected. Setting the current authority succeeds without
+/// writing or logging an event.
+///
+/// Accounts:
+/// 0. `[signer]` current authority
+/// 1. `[writable]` vault PDA account
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    new_authority: &[u8; 32],
+) -> ProgramResult {
+    let [authority, vault, ..] = accounts else {
+        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate_authority(program_id, authority, vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    if unsafe { Vault::from_account_unchecked(vault) }
+        .authority()
+        .as_ref()
+        == new_authority
+    {
+        return Ok(());
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    unsafe {
+        VaultMut::from_account_unchecked(vault).set_authority(&Address::from(*new_authority))
+    };
+
+    events::record(vault, "set_authority")?;
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultMut};
+use crate::utils::events;
+
+/// Process set-cosigner instruction
+///
+/// Sets the largest withdrawal the owner may make alone and the cosigner
+/// who must also sign anything above it. A limit of 0 removes the
+/// requirement. With a limit, the cosigner must be a real key other than
+/// the owner, or the instruction fails with `InvalidArgument`. Setting the
+/// current values succeeds without writing or logging an event.
+///
+/// Accounts:
+/// 0. `[signer]` authority
+/// 1. `[writable]` vault PDA account
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    single_sig_limit: u64,
+    cosigner: &[u8; 32],
+) -> ProgramResult {
+    let [authority, vault, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate_authority(program_id, authority, vault)?;
+
+    let cosigner = Address::from(*cosigner);
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    if single_sig_limit > 0 && (cosigner == Address::default() || &cosigner == vault_state.owner())
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if vault_state.single_sig_limit() == single_sig_limit && vault_state.cosigner() == &cosigner {
+        return Ok(());
+    }
+
+    // SAFETY: the read view above is no longer used
+    unsafe { VaultMut::from_account_unchecked(vault).set_cosigner(single_sig_limit, &cosigner) };
+
+    events::record(vault, "set_cosigner")?;
+
+    Ok(())
+}
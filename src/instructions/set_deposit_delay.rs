@@ -0,0 +1,34 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultMut};
+use crate::utils::events;
+
+/// Process set-deposit-delay instruction
+///
+/// Sets how many slots after creation the vault must age before deposits
+/// are accepted. Zero disables the delay. Setting the current value
+/// succeeds without writing or logging an event.
+///
+/// Accounts:
+/// 0. `[signer]` authority
+/// 1. `[writable]` vault PDA account
+pub fn handler(program_id: &Address, accounts: &[AccountView], slots: u64) -> ProgramResult {
+    let [authority, vault, ..] = accounts else {
+        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate_authority(program_id, authority, vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    if unsafe { Vault::from_account_unchecked(vault) }.deposit_delay_slots() == slots {
+        return Ok(());
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    unsafe { VaultMut::from_account_unchecked(vault).set_deposit_delay_slots(slots) };
+
+    events::record(vault, "set_deposit_delay")?;
+
+    Ok(())
+}
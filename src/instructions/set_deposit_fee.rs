@@ -0,0 +1,43 @@
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::instructions::{admin_reconcile, withdraw};
+use crate::state::vault::{Vault, VaultMut};
+use crate::utils::events;
+
+/// Process set-deposit-fee instruction
+///
+/// Sets the share of each deposit, in basis points, that is sent to the
+/// global config's treasury instead of the vault. Zero disables the fee.
+/// Setting the current value succeeds without writing or logging an event.
+///
+/// The fee is the protocol's revenue, so it is signed by the global config's
+/// admin rather than the vault's authority, who could otherwise waive it.
+///
+/// Accounts:
+/// 0. `[signer]` admin named by the global config
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` config PDA account
+pub fn handler(program_id: &Address, accounts: &[AccountView], fee_bps: u16) -> ProgramResult {
+    let [admin, vault, config, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    admin_reconcile::validate_admin(program_id, admin, config)?;
+
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    // SAFETY: no active borrows of vault data at this point
+    let current = unsafe { Vault::from_account(vault) }?.deposit_fee_bps();
+    withdraw::verify_stored_address(program_id, vault)?;
+    if current == fee_bps {
+        return Ok(());
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    unsafe { VaultMut::from_account_unchecked(vault).set_deposit_fee_bps(fee_bps) };
+
+    events::record(vault, "set_deposit_fee")?;
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultMut};
+use crate::utils::events;
+
+/// Vault option: reject a withdraw-type instruction if an earlier
+/// instruction in the same transaction already withdrew from this vault
+pub const OPTION_SINGLE_OP_PER_TX: u8 = 1 << 0;
+
+/// Vault option: reject deposits that are not a whole number of SOL
+pub const OPTION_WHOLE_UNIT_DEPOSITS: u8 = 1 << 1;
+
+/// Vault option: reject a withdraw-type instruction if an earlier
+/// instruction in the same transaction deposited into this vault
+pub const OPTION_NO_FLASH: u8 = 1 << 2;
+
+/// All vault options understood by this program
+pub const OPTIONS_MASK: u8 = OPTION_SINGLE_OP_PER_TX | OPTION_WHOLE_UNIT_DEPOSITS | OPTION_NO_FLASH;
+
+/// Process set-options instruction
+///
+/// Replaces the vault's option bits. Setting the current value succeeds
+/// without writing or logging an event.
+///
+/// Accounts:
+/// 0. `[signer]` authority
+/// 1. `[writable]` vault PDA account
+pub fn handler(program_id: &Address, accounts: &[AccountView], options: u8) -> ProgramResult {
+    let [authority, vault, ..] = accounts else {
+        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate_authority(program_id, authority, vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    if unsafe { Vault::from_account_unchecked(vault) }.options() == options {
+        return Ok(());
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    unsafe { VaultMut::from_account_unchecked(vault).set_options(options) };
+
+    events::record(vault, "set_options")?;
+
+    Ok(())
+}
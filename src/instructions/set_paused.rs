@@ -0,0 +1,35 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultMut};
+use crate::utils::events;
+
+/// Process set-paused instruction
+///
+/// Freezes or unfreezes the vault. While paused every deposit and
+/// withdraw-type instruction fails with `Paused`; `CloseVault` still runs so
+/// the owner can always recover the funds. Setting the current value
+/// succeeds without writing or logging an event.
+///
+/// Accounts:
+/// 0. `[signer]` authority
+/// 1. `[writable]` vault PDA account
+pub fn handler(program_id: &Address, accounts: &[AccountView], paused: bool) -> ProgramResult {
+    let [authority, vault, ..] = accounts else {
+        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate_authority(program_id, authority, vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    if unsafe { Vault::from_account_unchecked(vault) }.is_paused() == paused {
+        return Ok(());
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    unsafe { VaultMut::from_account_unchecked(vault).set_paused(paused) };
+
+    events::record(vault, "set_paused")?;
+
+    Ok(())
+}
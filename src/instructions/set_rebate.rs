@@ -0,0 +1,33 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultMut};
+use crate::utils::events;
+
+/// Process set-rebate instruction
+///
+/// Sets the fixed rebate paid to the fee payer by `WithdrawWithRebate`.
+/// Setting the current value succeeds without writing or logging an event.
+///
+/// Accounts:
+/// 0. `[signer]` authority
+/// 1. `[writable]` vault PDA account
+pub fn handler(program_id: &Address, accounts: &[AccountView], lamports: u64) -> ProgramResult {
+    let [authority, vault, ..] = accounts else {
+        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate_authority(program_id, authority, vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    if unsafe { Vault::from_account_unchecked(vault) }.rebate_lamports() == lamports {
+        return Ok(());
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    unsafe { VaultMut::from_account_unchecked(vault).set_rebate_lamports(lamports) };
+
+    events::record(vault, "set_rebate")?;
+
+    Ok(())
+}
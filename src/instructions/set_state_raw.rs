@@ -0,0 +1,41 @@
+use pinocchio::error::ProgramError;
+use pinocchio::{AccountView, Address, ProgramResult};
+
+/// Maximum number of bytes a single `SetStateRaw` may write
+pub const MAX_RAW_WRITE: usize = 32;
+
+/// Process set-state-raw instruction (test builds only)
+///
+/// Overwrites `bytes` at `offset` in the vault data with no authorization,
+/// so tests can construct states the production instructions never reach.
+/// Only compiled with the `test-hooks` feature; never ship a build with it.
+///
+/// Accounts:
+/// 0. `[writable]` vault PDA account
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    offset: u16,
+    bytes: &[u8],
+) -> ProgramResult {
+    let [vault, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Bound by the account itself: a tombstone is shorter than a vault
+    let start = offset as usize;
+    let end = start + bytes.len();
+    if end > vault.data_len() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    let data = unsafe { vault.borrow_unchecked_mut() };
+    data[start..end].copy_from_slice(bytes);
+
+    Ok(())
+}
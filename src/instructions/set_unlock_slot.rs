@@ -0,0 +1,48 @@
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::error::VaultError;
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultMut};
+use crate::utils::events;
+
+/// Process set-unlock-slot instruction
+///
+/// Sets the slot before which every withdraw-type instruction fails with
+/// `VaultLocked`. Zero removes the lock. While a lock is active it can only
+/// be extended, never brought forward, so the owner cannot release funds
+/// early. Setting the current value succeeds without writing or logging an
+/// event.
+///
+/// Only the owner may sign, not a delegated authority: a lock holds the
+/// owner's funds, and an authority that could extend it to `u64::MAX` would
+/// freeze them for good.
+///
+/// Accounts:
+/// 0. `[signer]` owner
+/// 1. `[writable]` vault PDA account
+pub fn handler(program_id: &Address, accounts: &[AccountView], slot: u64) -> ProgramResult {
+    let [owner, vault, ..] = accounts else {
+        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate(program_id, owner, vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    let current = vault_state.unlock_slot();
+    if current == slot {
+        return Ok(());
+    }
+
+    if slot < current && Clock::get()?.slot < current {
+        return Err(VaultError::VaultLocked.into());
+    }
+
+    // SAFETY: the read view above is no longer used
+    unsafe { VaultMut::from_account_unchecked(vault).set_unlock_slot(slot) };
+
+    events::record(vault, "set_unlock_slot")?;
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultAmount, VaultMut};
+use crate::utils::{events, helpers::vault_backing};
+
+/// Process sweep-dust instruction
+///
+/// Folds any lamports held by the vault beyond its rent-exempt minimum and
+/// tracked amount into the tracked amount, reconciling rounding drift and
+/// stray transfers. Does nothing if there is no dust.
+///
+/// Accounts:
+/// 0. `[signer]` owner
+/// 1. `[writable]` vault PDA account
+pub fn handler(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [owner, vault, ..] = accounts else {
+        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate(program_id, owner, vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    let current_amount = vault_state.amount();
+    let dust = (vault_backing(vault, &Rent::get()?)? as VaultAmount).saturating_sub(current_amount);
+
+    if dust == 0 {
+        return Ok(());
+    }
+
+    let new_amount = current_amount
+        .checked_add(dust)
+        .ok_or(pinocchio::error::ProgramError::ArithmeticOverflow)?;
+    // SAFETY: the read view above is no longer used
+    unsafe { VaultMut::from_account_unchecked(vault).set_amount(new_amount) };
+
+    events::record(vault, "sweep_dust")?;
+
+    Ok(())
+}
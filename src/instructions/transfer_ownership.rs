@@ -0,0 +1,51 @@
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultMut};
+use crate::utils::events;
+
+/// Process transfer-ownership instruction
+///
+/// Hands the vault's funds to `new_owner`. The PDA address does not change:
+/// it stays derived from the original owner (the stored seed owner) while
+/// the stored owner, which authorizes deposits and withdrawals, becomes
+/// `new_owner`. Only the authority may transfer. An authority that is the
+/// current owner moves to `new_owner` with it, so the previous owner keeps
+/// no way to pause, lock or reclaim the vault; a separate authority set with
+/// `SetAuthority` is left unchanged. Transferring to the current owner
+/// succeeds without writing or logging an event.
+///
+/// Accounts:
+/// 0. `[signer]` authority
+/// 1. `[writable]` vault PDA account
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    new_owner: &[u8; 32],
+) -> ProgramResult {
+    let [authority, vault, ..] = accounts else {
+        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+    };
+
+    withdraw::validate_authority(program_id, authority, vault)?;
+
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    let owner = vault_state.owner_bytes();
+    if owner == *new_owner {
+        return Ok(());
+    }
+    let moves_authority = *vault_state.authority().as_array() == owner;
+
+    let new_owner = Address::from(*new_owner);
+    // SAFETY: the read view above is no longer used
+    let mut vault_state = unsafe { VaultMut::from_account_unchecked(vault) };
+    vault_state.set_owner(&new_owner);
+    if moves_authority {
+        vault_state.set_authority(&new_owner);
+    }
+
+    events::record(vault, "transfer_ownership")?;
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use pinocchio::cpi::set_return_data;
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::state::vault::{Vault, VaultAmount, AMOUNT_LEN};
+use crate::utils::helpers::vault_backing;
+
+/// Size of the `Verify` return data
+pub const VERIFY_RETURN_LEN: usize = AMOUNT_LEN + 8 + 1;
+
+/// Process verify instruction
+///
+/// Reports the tracked amount alongside the lamports actually backing it
+/// (balance minus rent-exempt minimum), so clients can flag vaults pending
+/// reconciliation. Read only; intended to be simulated.
+///
+/// Return data: [tracked amount: VaultAmount, backing lamports: u64, stale: u8]
+///
+/// Accounts:
+/// 0. `[]` vault PDA account
+pub fn handler(program_id: &Address, accounts: &[AccountView]) -> ProgramResult {
+    let [vault, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    let amount = unsafe { Vault::from_account(vault) }?.amount();
+    let backing = vault_backing(vault, &Rent::get()?)?;
+
+    let mut data = [0u8; VERIFY_RETURN_LEN];
+    data[..AMOUNT_LEN].copy_from_slice(&amount.to_le_bytes());
+    data[AMOUNT_LEN..AMOUNT_LEN + 8].copy_from_slice(&backing.to_le_bytes());
+    data[AMOUNT_LEN + 8] = (amount != backing as VaultAmount) as u8;
+    set_return_data(&data);
+
+    Ok(())
+}
@@ -1,6 +1,18 @@
-use pinocchio::{AccountView, Address, ProgramResult};
+use pinocchio::sysvars::instructions::Instructions;
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
 
-use crate::state::vault::Vault;
+use crate::error::VaultError;
+use crate::instructions::discriminator;
+use crate::instructions::set_options::{OPTION_NO_FLASH, OPTION_SINGLE_OP_PER_TX};
+use crate::state::vault::{
+    create_vault_address, Vault, VaultAmount, VaultMut, VaultType, SAVINGS_LOCK_SLOTS,
+    VAULT_LABEL_LEN,
+};
+use crate::utils::events;
+use crate::utils::helpers::{
+    owner_account_check, stack_height, system_program_check, vault_backing,
+};
 
 /// Process withdraw instruction
 ///
@@ -8,58 +20,448 @@ use crate::state::vault::Vault;
 /// 0. `[signer, writable]` owner
 /// 1. `[writable]` vault PDA account
 /// 2. `[]` system_program
+/// 3. `[]` instructions sysvar (optional, required for allow-listed CPI withdraws
+///    and single-op-per-transaction and no-flash vaults)
+/// 4. `[signer]` escrow counterparty (optional, after the sysvar if passed,
+///    required for escrow vaults)
+/// 5. `[signer]` cosigner (optional, after the sysvar if passed, required
+///    above the single signature limit)
 pub fn handler(
     program_id: &Address,
     accounts: &[AccountView],
     amount: u64,
-    _bump: u8,
+    bump: u8,
+    label: &[u8; VAULT_LABEL_LEN],
 ) -> ProgramResult {
-    let [owner, vault, _system_program] = accounts else {
-        return Err(pinocchio::error::ProgramError::NotEnoughAccountKeys);
+    let [owner, vault, system_program, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Validate owner is signer
-    assert!(owner.is_signer(), "Owner must be signer");
+    // Checks run cheapest and least specific first, each with its own
+    // error: signer, system program, vault data, then owner and balance
+    validate_signer(owner)?;
+    system_program_check(system_program)?;
 
-    // Validate vault is owned by our program
-    assert!(
-        vault.owned_by(program_id),
-        "Vault not owned by this program"
+    validate_withdrawer(program_id, owner, vault)?;
+    // SAFETY: no active borrows of vault data at this point
+    if unsafe { Vault::from_account_unchecked(vault) }.label() != label {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    verify_address(program_id, vault, bump)?;
+    verify_stored_address(program_id, vault)?;
+    check_caller(vault, remaining.first())?;
+    check_single_op(program_id, vault, remaining.first())?;
+    check_no_flash(program_id, vault, remaining.first())?;
+    check_unlocked(vault)?;
+    check_vault_type(vault, remaining)?;
+    check_cosigner(vault, amount, remaining)?;
+
+    release(vault, owner, amount, &Rent::get()?)?;
+    count_withdrawal(vault)?;
+
+    events::log_balance_change(
+        "VAULT_WITHDRAW",
+        owner.address(),
+        amount,
+        // SAFETY: no active borrows of vault data at this point
+        unsafe { Vault::from_account_unchecked(vault) }.amount(),
     );
+    events::record(vault, "withdraw")?;
+
+    Ok(())
+}
+
+/// Validate that `owner` is a signable account and signed
+pub(crate) fn validate_signer(owner: &AccountView) -> ProgramResult {
+    owner_account_check(owner)?;
+    if !owner.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    Ok(())
+}
+
+/// Validate that `owner` signed and owns `vault`
+pub(crate) fn validate(
+    program_id: &Address,
+    owner: &AccountView,
+    vault: &AccountView,
+) -> ProgramResult {
+    validate_signer(owner)?;
+
+    // Validate vault is owned by our program before reading its data, so a
+    // foreign account with vault-looking bytes is rejected cleanly
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
 
     // Validate vault discriminator and owner
-    let vault_state = Vault::from_account(vault);
-    assert!(vault_state.owner() == owner.address(), "Owner mismatch");
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account(vault) }?;
+    if vault_state.owner_bytes() != *owner.address().as_array() {
+        return Err(VaultError::OwnerMismatch.into());
+    }
+
+    Ok(())
+}
+
+/// Validate that `authority` signed and is the authority of `vault`
+///
+/// Guards the instructions that change a vault's settings rather than move
+/// its funds. The authority starts out as the owner.
+pub(crate) fn validate_authority(
+    program_id: &Address,
+    authority: &AccountView,
+    vault: &AccountView,
+) -> ProgramResult {
+    owner_account_check(authority)?;
+    if !authority.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    if !vault.owned_by(program_id) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account(vault) }?;
+    if vault_state.authority() != authority.address() {
+        return Err(VaultError::AuthorityMismatch.into());
+    }
+
+    Ok(())
+}
 
-    // Check sufficient balance
+/// Validate that `signer` signed and may withdraw from `vault`
+///
+/// Like [`validate`], but a custody vault's delegate is accepted in place of
+/// the owner. Withdraw-type instructions pay the account in the owner slot,
+/// so a delegate receives the lamports it withdraws.
+pub(crate) fn validate_withdrawer(
+    program_id: &Address,
+    signer: &AccountView,
+    vault: &AccountView,
+) -> ProgramResult {
+    match validate(program_id, signer, vault) {
+        Err(err) if err == VaultError::OwnerMismatch.into() => {
+            // SAFETY: no active borrows of vault data at this point
+            let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+            if vault_state.vault_type() == VaultType::Custody
+                && vault_state.party() == signer.address()
+            {
+                Ok(())
+            } else {
+                Err(err)
+            }
+        }
+        result => result,
+    }
+}
+
+/// Apply the rules of the vault's `VaultType` to a withdraw-type instruction
+///
+/// A savings vault fails with `VaultLocked` until `SAVINGS_LOCK_SLOTS` have
+/// passed since creation. An escrow vault fails with `MissingCosigner`
+/// unless its counterparty is among `accounts` as a signer.
+pub(crate) fn check_vault_type(vault: &AccountView, accounts: &[AccountView]) -> ProgramResult {
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    match vault_state.vault_type() {
+        VaultType::Savings => {
+            let unlock_slot = vault_state
+                .created_slot()
+                .saturating_add(SAVINGS_LOCK_SLOTS);
+            if Clock::get()?.slot < unlock_slot {
+                return Err(VaultError::VaultLocked.into());
+            }
+        }
+        VaultType::Escrow => {
+            let party = vault_state.party();
+            if !accounts
+                .iter()
+                .any(|account| account.address() == party && account.is_signer())
+            {
+                return Err(VaultError::MissingCosigner.into());
+            }
+        }
+        VaultType::Standard | VaultType::Custody => {}
+    }
+
+    Ok(())
+}
+
+/// Require the vault's cosigner among `accounts` as a signer when `amount`
+/// is above its single signature limit
+///
+/// A limit of 0 needs no cosigner for any amount.
+pub(crate) fn check_cosigner(
+    vault: &AccountView,
+    amount: u64,
+    accounts: &[AccountView],
+) -> ProgramResult {
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    let limit = vault_state.single_sig_limit();
+    if limit == 0 || amount <= limit {
+        return Ok(());
+    }
+
+    let cosigner = vault_state.cosigner();
+    if !accounts
+        .iter()
+        .any(|account| account.address() == cosigner && account.is_signer())
+    {
+        return Err(VaultError::MissingCosigner.into());
+    }
+
+    Ok(())
+}
+
+/// Validate that an already validated `vault` is the PDA derived from its
+/// seed owner, its label and `bump`
+pub(crate) fn verify_address(program_id: &Address, vault: &AccountView, bump: u8) -> ProgramResult {
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    let derived = create_vault_address(
+        vault_state.seed_owner(),
+        vault_state.label(),
+        bump,
+        program_id,
+    );
+    if derived.as_ref() != Some(vault.address()) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(())
+}
+
+/// Validate that an already validated `vault` is the PDA derived from its
+/// seed owner, its label and its stored bump
+///
+/// Binds the account to the bump recorded at initialize rather than only
+/// the one the client passed.
+pub(crate) fn verify_stored_address(program_id: &Address, vault: &AccountView) -> ProgramResult {
+    verify_address(
+        program_id,
+        vault,
+        // SAFETY: no active borrows of vault data at this point
+        unsafe { Vault::from_account_unchecked(vault) }.bump(),
+    )
+}
+
+/// When called via CPI, require the calling program to be on the vault's
+/// allowed caller list
+///
+/// Direct withdraws are always permitted, as are CPI withdraws while the
+/// list is empty. The caller is read from the instructions sysvar, which is
+/// the top-level program, so only depth-1 CPIs can be attributed; deeper
+/// invocations are rejected.
+pub(crate) fn check_caller(
+    vault: &AccountView,
+    instructions_sysvar: Option<&AccountView>,
+) -> ProgramResult {
+    let height = stack_height();
+    if height <= 1 {
+        return Ok(());
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    let allowed = vault_state.allowed_callers();
+    if allowed.is_empty() {
+        return Ok(());
+    }
+
+    let sysvar = instructions_sysvar.ok_or(VaultError::UnauthorizedCaller)?;
+    let instructions = Instructions::try_from(sysvar)?;
+    let current = instructions.load_instruction_at(instructions.load_current_index() as usize)?;
+
+    if height != 2 || !allowed.contains(current.get_program_id()) {
+        return Err(VaultError::UnauthorizedCaller.into());
+    }
+
+    Ok(())
+}
+
+/// Discriminators of instructions that move lamports out of a vault
+pub(crate) const OUTFLOW_DISCRIMINATORS: [u8; 8] = [
+    discriminator::WITHDRAW,
+    discriminator::CLOSE_VAULT,
+    discriminator::WITHDRAW_ALL,
+    discriminator::WITHDRAW_TO,
+    discriminator::WITHDRAW_WITH_REBATE,
+    discriminator::WITHDRAW_PERCENT_OF_ACTUAL,
+    discriminator::WITHDRAW_SPLIT,
+    discriminator::REFUND,
+];
+
+/// With `OPTION_SINGLE_OP_PER_TX` set, reject this instruction if an earlier
+/// instruction in the transaction already moved lamports out of `vault`
+///
+/// Only top-level instructions are visible through the instructions sysvar,
+/// which must be passed when the option is set.
+pub(crate) fn check_single_op(
+    program_id: &Address,
+    vault: &AccountView,
+    instructions_sysvar: Option<&AccountView>,
+) -> ProgramResult {
+    // SAFETY: no active borrows of vault data at this point
+    if unsafe { Vault::from_account_unchecked(vault) }.options() & OPTION_SINGLE_OP_PER_TX == 0 {
+        return Ok(());
+    }
+
+    let sysvar = instructions_sysvar.ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let instructions = Instructions::try_from(sysvar)?;
+
+    for index in 0..instructions.load_current_index() as usize {
+        let instruction = instructions.load_instruction_at(index)?;
+        let is_outflow = instruction.get_program_id() == program_id
+            && instruction
+                .get_instruction_data()
+                .first()
+                .is_some_and(|d| OUTFLOW_DISCRIMINATORS.contains(d));
+        let same_vault = instruction
+            .get_instruction_account_at(1)
+            .is_ok_and(|account| &account.key == vault.address());
+
+        if is_outflow && same_vault {
+            return Err(VaultError::DuplicateVaultOperation.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Discriminators of instructions that move lamports into a vault
+pub(crate) const INFLOW_DISCRIMINATORS: [u8; 3] = [
+    discriminator::DEPOSIT,
+    discriminator::CONTRIBUTE,
+    discriminator::BATCH_DEPOSIT,
+];
+
+/// With `OPTION_NO_FLASH` set, reject this instruction if an earlier
+/// instruction in the transaction deposited into `vault`
+///
+/// Any account of a deposit counts, since `BatchDeposit` lists its vaults
+/// after the system program. Only top-level instructions are visible, so a
+/// deposit made through CPI is not detected.
+pub(crate) fn check_no_flash(
+    program_id: &Address,
+    vault: &AccountView,
+    instructions_sysvar: Option<&AccountView>,
+) -> ProgramResult {
+    // SAFETY: no active borrows of vault data at this point
+    if unsafe { Vault::from_account_unchecked(vault) }.options() & OPTION_NO_FLASH == 0 {
+        return Ok(());
+    }
+
+    let sysvar = instructions_sysvar.ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let instructions = Instructions::try_from(sysvar)?;
+
+    for index in 0..instructions.load_current_index() as usize {
+        let instruction = instructions.load_instruction_at(index)?;
+        let is_inflow = instruction.get_program_id() == program_id
+            && instruction
+                .get_instruction_data()
+                .first()
+                .is_some_and(|d| INFLOW_DISCRIMINATORS.contains(d));
+        let touches_vault = (0..instruction.num_account_metas()).any(|i| {
+            instruction
+                .get_instruction_account_at(i)
+                .is_ok_and(|account| &account.key == vault.address())
+        });
+
+        if is_inflow && touches_vault {
+            return Err(VaultError::SameTransactionDeposit.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Reject the withdraw while the current slot is before the vault's unlock
+/// slot
+pub(crate) fn check_unlocked(vault: &AccountView) -> ProgramResult {
+    // SAFETY: no active borrows of vault data at this point
+    let unlock_slot = unsafe { Vault::from_account_unchecked(vault) }.unlock_slot();
+    if unlock_slot > 0 && Clock::get()?.slot < unlock_slot {
+        return Err(VaultError::VaultLocked.into());
+    }
+
+    Ok(())
+}
+
+/// Move `amount` tracked lamports out of an already validated vault into
+/// `recipient` and update the stored amount
+///
+/// The payout is bounded by both the tracked amount and the backing: the
+/// vault always keeps its rent-exempt minimum, so a desynced tracked amount
+/// cannot brick the account, and lamports sent straight to the PDA are not
+/// tracked, so this never pays them out. `SweepDust` and
+/// `WithdrawPercentOfActual` first raise the tracked amount to the backing,
+/// and `CloseVault` pays out the whole balance without coming here.
+///
+/// Every withdraw-type instruction pays out through here, so a paused vault
+/// fails with `Paused` whichever one is used. `CloseVault` does not, and
+/// still runs while paused so funds cannot be trapped.
+pub(crate) fn release(
+    vault: &AccountView,
+    recipient: &AccountView,
+    amount: u64,
+    rent: &Rent,
+) -> ProgramResult {
+    // SAFETY: no active borrows of vault data at this point
+    let vault_state = unsafe { Vault::from_account_unchecked(vault) };
+    if vault_state.is_paused() {
+        return Err(VaultError::Paused.into());
+    }
+
+    // Check sufficient balance, both tracked and above the rent floor
     let current_amount = vault_state.amount();
-    assert!(current_amount >= amount, "Insufficient vault balance");
+    if current_amount < amount as VaultAmount || vault_backing(vault, rent)? < amount {
+        return Err(VaultError::InsufficientBalance.into());
+    }
 
     // Direct lamport manipulation instead of System Program Transfer.
     // The System Program refuses transfers from accounts that carry data,
     // so we directly debit/credit lamports. This is safe because the
     // vault is a PDA owned by our program.
     let vault_current_lamports = vault.lamports();
-    let owner_current_lamports = owner.lamports();
+    let recipient_current_lamports = recipient.lamports();
 
     vault.set_lamports(
         vault_current_lamports
             .checked_sub(amount)
-            .expect("Vault lamport underflow"),
+            .ok_or(ProgramError::ArithmeticOverflow)?,
     );
-    owner.set_lamports(
-        owner_current_lamports
+    recipient.set_lamports(
+        recipient_current_lamports
             .checked_add(amount)
-            .expect("Owner lamport overflow"),
+            .ok_or(ProgramError::ArithmeticOverflow)?,
     );
 
     // Update the stored amount
-    // SAFETY: no active borrows of vault data at this point
-    let data = unsafe { vault.borrow_unchecked_mut() };
     let new_amount = current_amount
-        .checked_sub(amount)
-        .expect("Withdraw underflow");
-    data[Vault::AMOUNT_OFFSET..Vault::AMOUNT_OFFSET + 8].copy_from_slice(&new_amount.to_le_bytes());
+        .checked_sub(amount as VaultAmount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    // SAFETY: the read view above is no longer used
+    unsafe { VaultMut::from_account_unchecked(vault).set_amount(new_amount) };
+
+    Ok(())
+}
+
+/// Count one withdrawal in the vault's lifetime total
+///
+/// Called once per instruction after its payouts, so a split or rebated
+/// withdraw counts once however many transfers it makes.
+pub(crate) fn count_withdrawal(vault: &AccountView) -> ProgramResult {
+    // SAFETY: no active borrows of vault data at this point
+    let withdraw_count = unsafe { Vault::from_account_unchecked(vault) }
+        .withdraw_count()
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    // SAFETY: no active borrows of vault data at this point
+    unsafe { VaultMut::from_account_unchecked(vault).set_withdraw_count(withdraw_count) };
 
     Ok(())
 }
@@ -0,0 +1,54 @@
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::error::VaultError;
+use crate::instructions::withdraw;
+use crate::state::vault::Vault;
+use crate::utils::events;
+use crate::utils::helpers::system_program_check;
+
+/// Process withdraw-all instruction
+///
+/// Withdraws the vault's entire tracked amount, so clients do not need to
+/// read the account first. The rent-exempt minimum stays in the vault.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program
+/// 3. `[]` instructions sysvar (optional, required for allow-listed CPI withdraws
+///    and single-op-per-transaction and no-flash vaults)
+/// 4. `[signer]` escrow counterparty (optional, after the sysvar if passed,
+///    required for escrow vaults)
+/// 5. `[signer]` cosigner (optional, after the sysvar if passed, required
+///    above the single signature limit)
+pub fn handler(program_id: &Address, accounts: &[AccountView], bump: u8) -> ProgramResult {
+    let [owner, vault, system_program, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    withdraw::validate_withdrawer(program_id, owner, vault)?;
+    withdraw::verify_address(program_id, vault, bump)?;
+    withdraw::verify_stored_address(program_id, vault)?;
+    withdraw::check_caller(vault, remaining.first())?;
+    withdraw::check_single_op(program_id, vault, remaining.first())?;
+    withdraw::check_no_flash(program_id, vault, remaining.first())?;
+    withdraw::check_unlocked(vault)?;
+    withdraw::check_vault_type(vault, remaining)?;
+
+    // A tracked amount beyond u64 cannot be backed by lamports. Identity
+    // conversion unless `wide-amount` is enabled.
+    #[allow(clippy::useless_conversion)]
+    // SAFETY: no active borrows of vault data at this point
+    let amount = u64::try_from(unsafe { Vault::from_account_unchecked(vault) }.amount())
+        .map_err(|_| VaultError::InsufficientBalance)?;
+    withdraw::check_cosigner(vault, amount, remaining)?;
+    withdraw::release(vault, owner, amount, &Rent::get()?)?;
+    withdraw::count_withdrawal(vault)?;
+
+    events::record(vault, "withdraw_all")?;
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use pinocchio::error::ProgramError;
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::state::vault::{Vault, VaultAmount, VaultMut};
+use crate::utils::{
+    events,
+    helpers::{system_program_check, vault_backing},
+};
+
+/// Basis points in 100%
+pub const MAX_BPS: u16 = 10_000;
+
+/// Process withdraw-percent-of-actual instruction
+///
+/// Withdraws `bps` basis points of the lamports actually backing the vault
+/// (balance minus rent-exempt minimum) rather than of the tracked amount,
+/// and leaves the tracked amount equal to the remaining backing. Real
+/// lamports are treated as the source of truth, so a desynced tracked
+/// amount is corrected as a side effect. A nonzero maximum balance still
+/// caps the reconciled amount: backing above it stays untracked and is not
+/// counted towards the share. `bps` is never zero, `unpack` rejects that.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program
+/// 3. `[]` instructions sysvar (optional, required for allow-listed CPI withdraws
+///    and single-op-per-transaction and no-flash vaults)
+/// 4. `[signer]` escrow counterparty (optional, after the sysvar if passed,
+///    required for escrow vaults)
+/// 5. `[signer]` cosigner (optional, after the sysvar if passed, required
+///    above the single signature limit)
+pub fn handler(program_id: &Address, accounts: &[AccountView], bps: u16) -> ProgramResult {
+    let [owner, vault, system_program, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    withdraw::validate_withdrawer(program_id, owner, vault)?;
+    // No bump in the instruction data, so the stored one is the only check
+    withdraw::verify_stored_address(program_id, vault)?;
+    withdraw::check_caller(vault, remaining.first())?;
+    withdraw::check_single_op(program_id, vault, remaining.first())?;
+    withdraw::check_no_flash(program_id, vault, remaining.first())?;
+    withdraw::check_unlocked(vault)?;
+    withdraw::check_vault_type(vault, remaining)?;
+
+    let rent = Rent::get()?;
+    let backing = vault_backing(vault, &rent)?;
+    // SAFETY: no active borrows of vault data at this point
+    let tracked = match unsafe { Vault::from_account_unchecked(vault) }.max_balance() {
+        0 => backing,
+        max_balance => backing.min(max_balance),
+    };
+    // Cannot overflow: tracked * 10_000 fits in u128
+    let amount = (tracked as u128 * bps as u128 / MAX_BPS as u128) as u64;
+    withdraw::check_cosigner(vault, amount, remaining)?;
+
+    // Reconcile to the real backing, then withdraw from it
+    // SAFETY: the read view above is no longer used
+    unsafe { VaultMut::from_account_unchecked(vault).set_amount(tracked as VaultAmount) };
+
+    withdraw::release(vault, owner, amount, &rent)?;
+    withdraw::count_withdrawal(vault)?;
+
+    events::record(vault, "withdraw_percent_of_actual")?;
+
+    Ok(())
+}
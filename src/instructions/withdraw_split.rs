@@ -0,0 +1,82 @@
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::instructions::withdraw_percent_of_actual::MAX_BPS;
+use crate::utils::events;
+use crate::utils::helpers::system_program_check;
+
+/// Maximum number of recipients in one split, capping compute
+pub const MAX_SPLIT_RECIPIENTS: usize = 4;
+
+/// Process withdraw-split instruction
+///
+/// Withdraws `amount` and distributes it across the recipient accounts,
+/// each receiving its `shares` entry in basis points. Shares are validated
+/// to sum to 10_000 when unpacked. Integer rounding dust goes to the first
+/// recipient, so exactly `amount` leaves the vault.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program
+/// 3. `[writable]` recipients, one per share, in share order
+/// 4. `[]` instructions sysvar (optional, after the recipients, required for
+///    allow-listed CPI withdraws, single-op-per-transaction and no-flash
+///    vaults)
+/// 5. `[signer]` escrow counterparty (optional, after the sysvar if passed,
+///    required for escrow vaults)
+/// 6. `[signer]` cosigner (optional, after the sysvar if passed, required
+///    above the single signature limit)
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    amount: u64,
+    bump: u8,
+    shares: &[u16],
+) -> ProgramResult {
+    let [owner, vault, system_program, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+    if remaining.len() < shares.len() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (recipients, remaining) = remaining.split_at(shares.len());
+    // Paying the vault itself would lower the tracked amount while no
+    // lamports leave
+    if recipients.iter().any(|r| r.address() == vault.address()) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    withdraw::validate_withdrawer(program_id, owner, vault)?;
+    withdraw::verify_address(program_id, vault, bump)?;
+    withdraw::verify_stored_address(program_id, vault)?;
+    withdraw::check_caller(vault, remaining.first())?;
+    withdraw::check_single_op(program_id, vault, remaining.first())?;
+    withdraw::check_no_flash(program_id, vault, remaining.first())?;
+    withdraw::check_unlocked(vault)?;
+    withdraw::check_vault_type(vault, remaining)?;
+    withdraw::check_cosigner(vault, amount, remaining)?;
+
+    // Cannot overflow: amount * 10_000 fits in u128
+    let share_of = |bps: u16| (amount as u128 * bps as u128 / MAX_BPS as u128) as u64;
+    let distributed: u64 = shares.iter().map(|&bps| share_of(bps)).sum();
+    let dust = amount - distributed;
+
+    let rent = Rent::get()?;
+    for (index, (recipient, &bps)) in recipients.iter().zip(shares).enumerate() {
+        let share = if index == 0 {
+            share_of(bps) + dust
+        } else {
+            share_of(bps)
+        };
+        withdraw::release(vault, recipient, share, &rent)?;
+    }
+    withdraw::count_withdrawal(vault)?;
+
+    events::record(vault, "withdraw_split")?;
+
+    Ok(())
+}
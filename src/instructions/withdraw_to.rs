@@ -0,0 +1,57 @@
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::instructions::withdraw;
+use crate::utils::events;
+use crate::utils::helpers::system_program_check;
+
+/// Process withdraw-to instruction
+///
+/// `Withdraw` that credits `recipient` instead of the owner, so the owner
+/// can pay someone straight from the vault. The owner still signs and must
+/// match the vault. The recipient may not be the vault itself.
+///
+/// Accounts:
+/// 0. `[signer]` owner
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program
+/// 3. `[writable]` recipient
+/// 4. `[]` instructions sysvar (optional, required for allow-listed CPI withdraws
+///    and single-op-per-transaction and no-flash vaults)
+/// 5. `[signer]` escrow counterparty (optional, after the sysvar if passed,
+///    required for escrow vaults)
+/// 6. `[signer]` cosigner (optional, after the sysvar if passed, required
+///    above the single signature limit)
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    amount: u64,
+    bump: u8,
+) -> ProgramResult {
+    let [owner, vault, system_program, recipient, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    withdraw::validate_withdrawer(program_id, owner, vault)?;
+    withdraw::verify_address(program_id, vault, bump)?;
+    withdraw::verify_stored_address(program_id, vault)?;
+    withdraw::check_caller(vault, remaining.first())?;
+    withdraw::check_single_op(program_id, vault, remaining.first())?;
+    withdraw::check_no_flash(program_id, vault, remaining.first())?;
+    withdraw::check_unlocked(vault)?;
+    withdraw::check_vault_type(vault, remaining)?;
+    withdraw::check_cosigner(vault, amount, remaining)?;
+
+    if recipient.address() == vault.address() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    withdraw::release(vault, recipient, amount, &Rent::get()?)?;
+    withdraw::count_withdrawal(vault)?;
+
+    events::record(vault, "withdraw_to")?;
+
+    Ok(())
+}
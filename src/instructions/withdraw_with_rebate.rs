@@ -0,0 +1,65 @@
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+use crate::error::VaultError;
+use crate::instructions::withdraw;
+use crate::state::vault::Vault;
+use crate::utils::events;
+use crate::utils::helpers::system_program_check;
+
+/// Process withdraw-with-rebate instruction
+///
+/// Withdraws `amount` to the owner, then pays the vault's configured
+/// `rebate_lamports` to the transaction fee payer to offset the fee they
+/// paid. Both amounts come out of the tracked balance.
+///
+/// Accounts:
+/// 0. `[signer, writable]` owner
+/// 1. `[writable]` vault PDA account
+/// 2. `[]` system_program
+/// 3. `[signer, writable]` fee payer
+/// 4. `[]` instructions sysvar (optional, required for allow-listed CPI withdraws
+///    and single-op-per-transaction and no-flash vaults)
+/// 5. `[signer]` escrow counterparty (optional, after the sysvar if passed,
+///    required for escrow vaults)
+/// 6. `[signer]` cosigner (optional, after the sysvar if passed, required
+///    above the single signature limit)
+pub fn handler(
+    program_id: &Address,
+    accounts: &[AccountView],
+    amount: u64,
+    bump: u8,
+) -> ProgramResult {
+    let [owner, vault, system_program, fee_payer, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    system_program_check(system_program)?;
+
+    withdraw::validate_withdrawer(program_id, owner, vault)?;
+    withdraw::verify_address(program_id, vault, bump)?;
+    withdraw::verify_stored_address(program_id, vault)?;
+    withdraw::check_caller(vault, remaining.first())?;
+    withdraw::check_single_op(program_id, vault, remaining.first())?;
+    withdraw::check_no_flash(program_id, vault, remaining.first())?;
+    withdraw::check_unlocked(vault)?;
+    withdraw::check_vault_type(vault, remaining)?;
+
+    // The fee payer always signs the transaction
+    if !fee_payer.is_signer() {
+        return Err(VaultError::NotSigner.into());
+    }
+
+    // SAFETY: no active borrows of vault data at this point
+    let rebate = unsafe { Vault::from_account_unchecked(vault) }.rebate_lamports();
+    withdraw::check_cosigner(vault, amount.saturating_add(rebate), remaining)?;
+
+    let rent = Rent::get()?;
+    withdraw::release(vault, owner, amount, &rent)?;
+    withdraw::release(vault, fee_payer, rebate, &rent)?;
+    withdraw::count_withdrawal(vault)?;
+
+    events::record(vault, "withdraw_with_rebate")?;
+
+    Ok(())
+}
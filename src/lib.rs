@@ -1,4 +1,7 @@
+#[cfg(feature = "client")]
+pub mod client;
 pub mod entrypoint;
+pub mod error;
 pub mod instructions;
 pub mod processor;
 pub mod state;
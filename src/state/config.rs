@@ -0,0 +1,109 @@
+use pinocchio::{cpi::Seed, error::ProgramError, AccountView, Address};
+
+use crate::error::VaultError;
+
+/// Config account discriminator
+pub const CONFIG_DISCRIMINATOR: [u8; 8] = [0x43, 0x6f, 0x6e, 0x66, 0x69, 0x67, 0x21, 0x21]; // "Config!!"
+
+/// Seed of the global config PDA
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Signer seeds of the config PDA: `[b"config", bump]`
+pub fn config_signer_seeds(bump: &[u8; 1]) -> [Seed<'_>; 2] {
+    [Seed::from(CONFIG_SEED), Seed::from(bump)]
+}
+
+/// Find the canonical config PDA and bump
+pub fn find_config_address(program_id: &Address) -> (Address, u8) {
+    Address::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+/// Global config account layout:
+/// - [0..8]    discriminator (8 bytes)
+/// - [8..40]   admin (32 bytes)
+/// - [40..72]  treasury (32 bytes)
+/// - [72..80]  creation fee in lamports (8 bytes, u64 LE)
+/// - [80]      PDA bump (1 byte)
+pub struct Config<'a>(&'a [u8]);
+
+impl<'a> Config<'a> {
+    pub const LEN: usize = Self::BUMP_OFFSET + 1; // 81 bytes
+
+    pub const DISCRIMINATOR_OFFSET: usize = 0;
+    pub const ADMIN_OFFSET: usize = 8;
+    pub const TREASURY_OFFSET: usize = Self::ADMIN_OFFSET + 32;
+    pub const CREATION_FEE_OFFSET: usize = Self::TREASURY_OFFSET + 32;
+    pub const BUMP_OFFSET: usize = Self::CREATION_FEE_OFFSET + 8;
+
+    /// Create a Config from an AccountView reference
+    ///
+    /// The length is not checked up front; an accessor whose field lies past
+    /// the end of the data panics instead of reading out of bounds.
+    ///
+    /// # Safety
+    /// The account data must not be mutably borrowed while the returned view
+    /// is alive.
+    pub unsafe fn from_account_unchecked(account: &'a AccountView) -> Self {
+        // SAFETY: the caller rules out a live mutable borrow
+        unsafe { Self(account.borrow_unchecked()) }
+    }
+
+    /// Create a Config from an AccountView, checking discriminator and length
+    ///
+    /// As with [`Vault::from_slice`](crate::state::Vault::from_slice), data
+    /// longer than [`Config::LEN`] is accepted and only the known prefix read.
+    ///
+    /// # Safety
+    /// Same as [`Config::from_account_unchecked`].
+    pub unsafe fn from_account(account: &'a AccountView) -> Result<Self, ProgramError> {
+        if account.data_len() < Self::LEN {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        // SAFETY: forwarded from the caller
+        let config = unsafe { Self::from_account_unchecked(account) };
+        if config.discriminator() != CONFIG_DISCRIMINATOR {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        Ok(config)
+    }
+
+    /// Fixed-size field at `offset`
+    fn field<const N: usize>(&self, offset: usize) -> &'a [u8; N] {
+        let data: &'a [u8] = self.0;
+        data[offset..offset + N].try_into().unwrap()
+    }
+
+    /// Address field at `offset`
+    fn address(&self, offset: usize) -> &'a Address {
+        let bytes: &'a [u8; 32] = self.field(offset);
+        // SAFETY: `Address` is a transparent wrapper over `[u8; 32]`
+        unsafe { &*(bytes as *const [u8; 32] as *const Address) }
+    }
+
+    /// Get the discriminator
+    pub fn discriminator(&self) -> [u8; 8] {
+        *self.field(Self::DISCRIMINATOR_OFFSET)
+    }
+
+    /// Get the admin pubkey
+    pub fn admin(&self) -> &'a Address {
+        self.address(Self::ADMIN_OFFSET)
+    }
+
+    /// Get the treasury pubkey
+    pub fn treasury(&self) -> &'a Address {
+        self.address(Self::TREASURY_OFFSET)
+    }
+
+    /// Get the lamports charged per vault creation, on top of rent (u64)
+    pub fn creation_fee(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::CREATION_FEE_OFFSET))
+    }
+
+    /// Get the PDA bump
+    pub fn bump(&self) -> u8 {
+        self.0[Self::BUMP_OFFSET]
+    }
+}
@@ -1,3 +1,11 @@
+pub mod config;
+pub mod receipt;
+pub mod tombstone;
 pub mod vault;
+pub mod vault_counter;
 
+pub use config::*;
+pub use receipt::*;
+pub use tombstone::*;
 pub use vault::*;
+pub use vault_counter::*;
@@ -0,0 +1,116 @@
+use pinocchio::{cpi::Seed, error::ProgramError, AccountView, Address};
+
+use crate::error::VaultError;
+
+/// Deposit receipt account discriminator
+pub const RECEIPT_DISCRIMINATOR: [u8; 8] = *b"Receipt!";
+
+/// Seed prefix of the deposit receipt PDA
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+
+/// Signer seeds of the receipt PDA: `[b"receipt", vault, depositor, bump]`
+pub fn receipt_signer_seeds<'a>(
+    vault: &'a Address,
+    depositor: &'a Address,
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 4] {
+    [
+        Seed::from(RECEIPT_SEED),
+        Seed::from(vault.as_ref()),
+        Seed::from(depositor.as_ref()),
+        Seed::from(bump),
+    ]
+}
+
+/// Derive the receipt PDA of `depositor` in `vault` with a stored `bump`
+///
+/// Returns `None` if the seeds land on the curve.
+pub fn create_receipt_address(
+    vault: &Address,
+    depositor: &Address,
+    bump: u8,
+    program_id: &Address,
+) -> Option<Address> {
+    Address::create_program_address(
+        &[RECEIPT_SEED, vault.as_ref(), depositor.as_ref(), &[bump]],
+        program_id,
+    )
+    .ok()
+}
+
+/// Find the canonical receipt PDA and bump of `depositor` in `vault`
+pub fn find_receipt_address(
+    vault: &Address,
+    depositor: &Address,
+    program_id: &Address,
+) -> (Address, u8) {
+    Address::find_program_address(
+        &[RECEIPT_SEED, vault.as_ref(), depositor.as_ref()],
+        program_id,
+    )
+}
+
+/// Deposit receipt account layout, one per vault and contributor, recording
+/// what `Refund` may still return to that contributor:
+/// - [0..8]   discriminator, `RECEIPT_DISCRIMINATOR` (8 bytes)
+/// - [8..16]  refundable lamports (8 bytes, u64 LE)
+/// - [16]     PDA bump (1 byte)
+pub struct Receipt<'a>(&'a [u8]);
+
+impl<'a> Receipt<'a> {
+    pub const LEN: usize = Self::BUMP_OFFSET + 1; // 17 bytes
+
+    pub const DISCRIMINATOR_OFFSET: usize = 0;
+    pub const REFUNDABLE_OFFSET: usize = 8;
+    pub const BUMP_OFFSET: usize = Self::REFUNDABLE_OFFSET + 8;
+
+    /// Create a Receipt from an AccountView, checking discriminator and
+    /// length
+    ///
+    /// # Safety
+    /// The account data must not be mutably borrowed while the returned view
+    /// is alive.
+    pub unsafe fn from_account(account: &'a AccountView) -> Result<Self, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        // SAFETY: the caller rules out a live mutable borrow
+        let receipt = Self(unsafe { account.borrow_unchecked() });
+        if receipt.discriminator() != RECEIPT_DISCRIMINATOR {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        Ok(receipt)
+    }
+
+    /// Fixed-size field at `offset`
+    fn field<const N: usize>(&self, offset: usize) -> &'a [u8; N] {
+        let data: &'a [u8] = self.0;
+        data[offset..offset + N].try_into().unwrap()
+    }
+
+    /// Get the discriminator
+    pub fn discriminator(&self) -> [u8; 8] {
+        *self.field(Self::DISCRIMINATOR_OFFSET)
+    }
+
+    /// Get the lamports `Refund` may still return to the depositor (u64)
+    pub fn refundable(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::REFUNDABLE_OFFSET))
+    }
+
+    /// Get the PDA bump
+    pub fn bump(&self) -> u8 {
+        self.0[Self::BUMP_OFFSET]
+    }
+
+    /// Write a receipt into `data`, which must be exactly [`Receipt::LEN`]
+    /// bytes
+    pub fn write(data: &mut [u8], refundable: u64, bump: u8) {
+        data[Self::DISCRIMINATOR_OFFSET..Self::REFUNDABLE_OFFSET]
+            .copy_from_slice(&RECEIPT_DISCRIMINATOR);
+        data[Self::REFUNDABLE_OFFSET..Self::BUMP_OFFSET].copy_from_slice(&refundable.to_le_bytes());
+        data[Self::BUMP_OFFSET] = bump;
+    }
+}
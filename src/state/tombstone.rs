@@ -0,0 +1,96 @@
+use pinocchio::{error::ProgramError, AccountView, Address};
+
+use crate::error::VaultError;
+use crate::state::vault::CLOSED_DISCRIMINATOR;
+
+/// Tombstone account layout, what `CloseVault` leaves behind when given a
+/// grace period:
+/// - [0..8]   discriminator, `CLOSED_DISCRIMINATOR` (8 bytes)
+/// - [8..40]  owner at close (32 bytes)
+/// - [40..48] slot the vault was closed in (8 bytes, u64 LE)
+/// - [48..56] grace period in slots during which `Reopen` may restore the
+///   vault (8 bytes, u64 LE)
+pub struct Tombstone<'a>(&'a [u8]);
+
+impl<'a> Tombstone<'a> {
+    pub const LEN: usize = Self::GRACE_SLOTS_OFFSET + 8; // 56 bytes
+
+    pub const DISCRIMINATOR_OFFSET: usize = 0;
+    pub const OWNER_OFFSET: usize = 8;
+    pub const CLOSE_SLOT_OFFSET: usize = Self::OWNER_OFFSET + 32;
+    pub const GRACE_SLOTS_OFFSET: usize = Self::CLOSE_SLOT_OFFSET + 8;
+
+    /// Create a Tombstone from an AccountView reference
+    ///
+    /// The length is not checked up front; an accessor whose field lies past
+    /// the end of the data panics instead of reading out of bounds.
+    ///
+    /// # Safety
+    /// The account data must not be mutably borrowed while the returned view
+    /// is alive.
+    pub unsafe fn from_account_unchecked(account: &'a AccountView) -> Self {
+        // SAFETY: the caller rules out a live mutable borrow
+        unsafe { Self(account.borrow_unchecked()) }
+    }
+
+    /// Create a Tombstone from an AccountView, checking discriminator and
+    /// length
+    ///
+    /// Unlike vaults, the length must match exactly: a closed vault without a
+    /// tombstone also carries `CLOSED_DISCRIMINATOR`, but no other fields.
+    ///
+    /// # Safety
+    /// Same as [`Tombstone::from_account_unchecked`].
+    pub unsafe fn from_account(account: &'a AccountView) -> Result<Self, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        // SAFETY: forwarded from the caller
+        let tombstone = unsafe { Self::from_account_unchecked(account) };
+        if tombstone.discriminator() != CLOSED_DISCRIMINATOR {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        Ok(tombstone)
+    }
+
+    /// Fixed-size field at `offset`
+    fn field<const N: usize>(&self, offset: usize) -> &'a [u8; N] {
+        let data: &'a [u8] = self.0;
+        data[offset..offset + N].try_into().unwrap()
+    }
+
+    /// Get the discriminator
+    pub fn discriminator(&self) -> [u8; 8] {
+        *self.field(Self::DISCRIMINATOR_OFFSET)
+    }
+
+    /// Get the owner the vault had when it was closed
+    pub fn owner(&self) -> &'a Address {
+        let bytes: &'a [u8; 32] = self.field(Self::OWNER_OFFSET);
+        // SAFETY: `Address` is a transparent wrapper over `[u8; 32]`
+        unsafe { &*(bytes as *const [u8; 32] as *const Address) }
+    }
+
+    /// Get the slot the vault was closed in (u64)
+    pub fn close_slot(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::CLOSE_SLOT_OFFSET))
+    }
+
+    /// Get the number of slots after the close during which the vault can be
+    /// reopened (u64)
+    pub fn grace_slots(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::GRACE_SLOTS_OFFSET))
+    }
+
+    /// Write a tombstone for `owner` into `data`, which must be exactly
+    /// [`Tombstone::LEN`] bytes
+    pub fn write(data: &mut [u8], owner: &Address, close_slot: u64, grace_slots: u64) {
+        data[Self::DISCRIMINATOR_OFFSET..Self::OWNER_OFFSET].copy_from_slice(&CLOSED_DISCRIMINATOR);
+        data[Self::OWNER_OFFSET..Self::CLOSE_SLOT_OFFSET].copy_from_slice(owner.as_ref());
+        data[Self::CLOSE_SLOT_OFFSET..Self::GRACE_SLOTS_OFFSET]
+            .copy_from_slice(&close_slot.to_le_bytes());
+        data[Self::GRACE_SLOTS_OFFSET..Self::LEN].copy_from_slice(&grace_slots.to_le_bytes());
+    }
+}
@@ -1,56 +1,722 @@
-use pinocchio::{AccountView, Address};
+use pinocchio::{cpi::Seed, error::ProgramError, AccountView, Address};
 
-/// Vault account discriminator
-pub const VAULT_DISCRIMINATOR: [u8; 8] = [0x56, 0x61, 0x75, 0x6c, 0x74, 0x21, 0x21, 0x21]; // "Vault!!!"
+use crate::error::VaultError;
 
-/// Vault state account layout:
+/// Default vault account discriminator
+pub const DEFAULT_VAULT_DISCRIMINATOR: [u8; 8] = *b"Vault!!!";
+
+/// Vault account discriminator, taken from the `VAULT_DISCRIMINATOR`
+/// environment variable at compile time (exactly 8 bytes) so a fork's vaults
+/// do not collide with canonical ones, or `DEFAULT_VAULT_DISCRIMINATOR`
+pub const VAULT_DISCRIMINATOR: [u8; 8] = match option_env!("VAULT_DISCRIMINATOR") {
+    Some(magic) => parse_discriminator(magic),
+    None => DEFAULT_VAULT_DISCRIMINATOR,
+};
+
+/// Parse the configured discriminator at compile time, failing the build if
+/// it is not exactly 8 bytes
+const fn parse_discriminator(magic: &str) -> [u8; 8] {
+    let bytes = magic.as_bytes();
+    assert!(
+        bytes.len() == 8,
+        "VAULT_DISCRIMINATOR must be exactly 8 bytes"
+    );
+    let mut discriminator = [0u8; 8];
+    let mut i = 0;
+    while i < 8 {
+        discriminator[i] = bytes[i];
+        i += 1;
+    }
+    discriminator
+}
+
+/// Discriminator written over a closed vault
+pub const CLOSED_DISCRIMINATOR: [u8; 8] = [0xff; 8];
+
+/// Version of the program build, `major << 16 | minor << 8 | patch`, taken
+/// from `CARGO_PKG_VERSION` at compile time
+pub const PROGRAM_VERSION: u32 = (parse_version_part(env!("CARGO_PKG_VERSION_MAJOR")) << 16)
+    | (parse_version_part(env!("CARGO_PKG_VERSION_MINOR")) << 8)
+    | parse_version_part(env!("CARGO_PKG_VERSION_PATCH"));
+
+/// Parse one decimal component of the crate version at compile time
+const fn parse_version_part(part: &str) -> u32 {
+    let bytes = part.as_bytes();
+    let mut value = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        value = value * 10 + (bytes[i] - b'0') as u32;
+        i += 1;
+    }
+    value
+}
+
+/// Seed prefix of the vault PDA
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Length of a vault label
+pub const VAULT_LABEL_LEN: usize = 16;
+
+/// Label of an owner's default vault, used when an instruction omits one
+pub const DEFAULT_VAULT_LABEL: [u8; VAULT_LABEL_LEN] = [0; VAULT_LABEL_LEN];
+
+/// Seed bytes contributed by `label`
+///
+/// The default label contributes an empty seed, which hashes the same as no
+/// seed at all, so default vaults keep the `[b"vault", owner]` address they
+/// had before labels existed.
+pub fn label_seed(label: &[u8; VAULT_LABEL_LEN]) -> &[u8] {
+    if label == &DEFAULT_VAULT_LABEL {
+        &[]
+    } else {
+        label
+    }
+}
+
+/// Signer seeds of the vault PDA: `[b"vault", owner, label, bump]`
+///
+/// This is the single definition of the vault seed scheme. Any instruction
+/// that signs for the vault must build its seeds here so the on-chain
+/// derivation cannot drift from the client's `find_program_address` call.
+pub fn vault_signer_seeds<'a>(
+    owner: &'a Address,
+    label: &'a [u8; VAULT_LABEL_LEN],
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 4] {
+    [
+        Seed::from(VAULT_SEED),
+        Seed::from(owner.as_ref()),
+        Seed::from(label_seed(label)),
+        Seed::from(bump),
+    ]
+}
+
+/// Derive the vault PDA for `owner` and `label` with a caller-supplied `bump`
+///
+/// Returns `None` if the seeds land on the curve.
+pub fn create_vault_address(
+    owner: &Address,
+    label: &[u8; VAULT_LABEL_LEN],
+    bump: u8,
+    program_id: &Address,
+) -> Option<Address> {
+    Address::create_program_address(
+        &[VAULT_SEED, owner.as_ref(), label_seed(label), &[bump]],
+        program_id,
+    )
+    .ok()
+}
+
+/// Find the canonical vault PDA and bump for `owner` and `label`
+pub fn find_vault_address(
+    owner: &Address,
+    label: &[u8; VAULT_LABEL_LEN],
+    program_id: &Address,
+) -> (Address, u8) {
+    Address::find_program_address(&[VAULT_SEED, owner.as_ref(), label_seed(label)], program_id)
+}
+
+/// Integer type of the vault's tracked amount
+///
+/// `u64` by default. The `wide-amount` feature swaps it for `u128`, widening
+/// the stored field to 16 bytes and shifting every later offset by 8.
+#[cfg(not(feature = "wide-amount"))]
+pub type VaultAmount = u64;
+
+/// Integer type of the vault's tracked amount
+///
+/// `u64` by default. The `wide-amount` feature swaps it for `u128`, widening
+/// the stored field to 16 bytes and shifting every later offset by 8.
+#[cfg(feature = "wide-amount")]
+pub type VaultAmount = u128;
+
+/// Serialized width of [`VaultAmount`] in bytes
+pub const AMOUNT_LEN: usize = core::mem::size_of::<VaultAmount>();
+
+/// Rule set a vault was created with, selected by `Initialize`
+///
+/// Each type bundles behaviors applied by the withdraw-type instructions on
+/// top of the per-vault settings. The type, and the `party` key the escrow
+/// and custody types need, are fixed for the vault's lifetime.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VaultType {
+    /// No extra rules
+    Standard = 0,
+    /// Withdrawals are locked for [`SAVINGS_LOCK_SLOTS`] after creation
+    Savings = 1,
+    /// Withdrawals also need the counterparty (`party`) to co-sign
+    Escrow = 2,
+    /// A delegate (`party`) may withdraw in the owner's place
+    Custody = 3,
+}
+
+impl VaultType {
+    /// Whether the type needs a `party` key
+    pub fn has_party(self) -> bool {
+        matches!(self, Self::Escrow | Self::Custody)
+    }
+}
+
+impl TryFrom<u8> for VaultType {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Standard),
+            1 => Ok(Self::Savings),
+            2 => Ok(Self::Escrow),
+            3 => Ok(Self::Custody),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/// Slots after creation before a savings vault accepts withdrawals,
+/// roughly one day at 400ms slots
+pub const SAVINGS_LOCK_SLOTS: u64 = 216_000;
+
+/// Owned copy of the vault header: discriminator, owner and amount
+///
+/// Serializes in the same order and encoding Borsh uses for these fields
+/// (fixed arrays as raw bytes, integers little-endian), so it is the one
+/// definition clients and the program share for the first
+/// [`VaultData::LEN`] bytes of the account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VaultData {
+    pub discriminator: [u8; 8],
+    pub owner: [u8; 32],
+    pub amount: VaultAmount,
+}
+
+impl VaultData {
+    /// Serialized size: 48 bytes, 56 with wide-amount
+    pub const LEN: usize = 8 + 32 + AMOUNT_LEN;
+
+    /// Write the header into the start of `data`
+    ///
+    /// Panics if `data` is shorter than [`VaultData::LEN`].
+    pub fn pack_into(&self, data: &mut [u8]) {
+        data[Vault::DISCRIMINATOR_OFFSET..Vault::OWNER_OFFSET].copy_from_slice(&self.discriminator);
+        data[Vault::OWNER_OFFSET..Vault::AMOUNT_OFFSET].copy_from_slice(&self.owner);
+        data[Vault::AMOUNT_OFFSET..Self::LEN].copy_from_slice(&self.amount.to_le_bytes());
+    }
+
+    /// Read the header from the start of `data`, checking its length and
+    /// vault discriminator
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+
+        let vault = Vault(data);
+        if vault.discriminator() != VAULT_DISCRIMINATOR {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        Ok(Self {
+            discriminator: vault.discriminator(),
+            owner: *vault.owner().as_array(),
+            amount: vault.amount(),
+        })
+    }
+}
+
+/// Vault state account layout (default `u64` amount; offsets after the
+/// amount shift by 8 with `wide-amount`):
 /// - [0..8]   discriminator (8 bytes)
 /// - [8..40]  owner (32 bytes)
-/// - [40..48] amount (8 bytes, u64 LE)
-pub struct Vault(*const u8);
+/// - [40..48] amount (`AMOUNT_LEN` bytes, `VaultAmount` LE)
+/// - [48..80] last depositor (32 bytes)
+/// - [80..84] depositor changes (4 bytes, u32 LE)
+/// - [84..92] rebate lamports (8 bytes, u64 LE)
+/// - [92..100] event sequence number (8 bytes, u64 LE)
+/// - [100]      allowed CPI caller count (1 byte)
+/// - [101..229] allowed CPI caller program IDs (4 x 32 bytes)
+/// - [229..237] creation slot (8 bytes, u64 LE)
+/// - [237..245] deposit delay in slots after creation (8 bytes, u64 LE)
+/// - [245..277] seed owner, the key the PDA was derived from (32 bytes)
+/// - [277]      PDA bump (1 byte)
+/// - [278]      option bits (1 byte)
+/// - [279..283] program version that created the vault (4 bytes, u32 LE)
+/// - [283..299] label, the PDA's name among the seed owner's vaults (16 bytes)
+/// - [299..307] slot before which withdrawals are rejected (8 bytes, u64 LE)
+/// - [307..315] maximum tracked amount, 0 for unlimited (8 bytes, u64 LE)
+/// - [315]      paused flag, 1 blocks deposits and withdrawals (1 byte)
+/// - [316]      vault type, a `VaultType` (1 byte)
+/// - [317..349] party, the escrow counterparty or custody delegate (32 bytes)
+/// - [349..381] authority, the key allowed to change the vault's settings
+///   (32 bytes)
+/// - [381..389] lifetime deposit count (8 bytes, u64 LE)
+/// - [389..397] lifetime withdrawal count (8 bytes, u64 LE)
+/// - [397..405] minimum deposit, 0 for none (8 bytes, u64 LE)
+/// - [405..407] deposit fee in basis points, 0 for none (2 bytes, u16 LE)
+/// - [407..415] largest withdrawal the owner may sign alone, 0 for no limit
+///   (8 bytes, u64 LE)
+/// - [415..447] cosigner required above that limit (32 bytes)
+pub struct Vault<'a>(&'a [u8]);
+
+/// Mutable view over the vault state, for instructions that write it
+///
+/// Reads go through [`VaultMut::view`], which reborrows the same bytes as a
+/// [`Vault`], so a write is always visible to the next read.
+pub struct VaultMut<'a>(&'a mut [u8]);
 
-impl Vault {
-    pub const LEN: usize = 8 + 32 + 8; // 48 bytes
+impl<'a> Vault<'a> {
+    pub const LEN: usize = Self::COSIGNER_OFFSET + 32; // 447 bytes, 455 with wide-amount
 
-    pub const DISCRIMINATOR_OFFSET: usize = 0;
-    pub const OWNER_OFFSET: usize = 8;
-    pub const AMOUNT_OFFSET: usize = 40;
+    /// Maximum number of programs allowed to withdraw via CPI
+    pub const MAX_ALLOWED_CALLERS: usize = 4;
+
+    // Each offset follows the previous field, so widening the amount moves
+    // everything after it
+    const DISCRIMINATOR_OFFSET: usize = 0;
+    const OWNER_OFFSET: usize = 8;
+    const AMOUNT_OFFSET: usize = 40;
+    const LAST_DEPOSITOR_OFFSET: usize = Self::AMOUNT_OFFSET + AMOUNT_LEN;
+    const DEPOSITOR_CHANGES_OFFSET: usize = Self::LAST_DEPOSITOR_OFFSET + 32;
+    const REBATE_LAMPORTS_OFFSET: usize = Self::DEPOSITOR_CHANGES_OFFSET + 4;
+    const SEQ_OFFSET: usize = Self::REBATE_LAMPORTS_OFFSET + 8;
+    const ALLOWED_CALLERS_COUNT_OFFSET: usize = Self::SEQ_OFFSET + 8;
+    const ALLOWED_CALLERS_OFFSET: usize = Self::ALLOWED_CALLERS_COUNT_OFFSET + 1;
+    const CREATED_SLOT_OFFSET: usize =
+        Self::ALLOWED_CALLERS_OFFSET + 32 * Self::MAX_ALLOWED_CALLERS;
+    const DEPOSIT_DELAY_SLOTS_OFFSET: usize = Self::CREATED_SLOT_OFFSET + 8;
+    const SEED_OWNER_OFFSET: usize = Self::DEPOSIT_DELAY_SLOTS_OFFSET + 8;
+    const BUMP_OFFSET: usize = Self::SEED_OWNER_OFFSET + 32;
+    const OPTIONS_OFFSET: usize = Self::BUMP_OFFSET + 1;
+    const CREATED_BY_VERSION_OFFSET: usize = Self::OPTIONS_OFFSET + 1;
+    const LABEL_OFFSET: usize = Self::CREATED_BY_VERSION_OFFSET + 4;
+    const UNLOCK_SLOT_OFFSET: usize = Self::LABEL_OFFSET + VAULT_LABEL_LEN;
+    const MAX_BALANCE_OFFSET: usize = Self::UNLOCK_SLOT_OFFSET + 8;
+    const PAUSED_OFFSET: usize = Self::MAX_BALANCE_OFFSET + 8;
+    const VAULT_TYPE_OFFSET: usize = Self::PAUSED_OFFSET + 1;
+    const PARTY_OFFSET: usize = Self::VAULT_TYPE_OFFSET + 1;
+    const AUTHORITY_OFFSET: usize = Self::PARTY_OFFSET + 32;
+    const DEPOSIT_COUNT_OFFSET: usize = Self::AUTHORITY_OFFSET + 32;
+    const WITHDRAW_COUNT_OFFSET: usize = Self::DEPOSIT_COUNT_OFFSET + 8;
+    const MIN_DEPOSIT_OFFSET: usize = Self::WITHDRAW_COUNT_OFFSET + 8;
+    const DEPOSIT_FEE_BPS_OFFSET: usize = Self::MIN_DEPOSIT_OFFSET + 8;
+    const SINGLE_SIG_LIMIT_OFFSET: usize = Self::DEPOSIT_FEE_BPS_OFFSET + 2;
+    const COSIGNER_OFFSET: usize = Self::SINGLE_SIG_LIMIT_OFFSET + 8;
 
     /// Create a Vault from an AccountView reference
     ///
+    /// The length is not checked up front; an accessor whose field lies past
+    /// the end of the data panics instead of reading out of bounds.
+    ///
     /// # Safety
-    /// The caller must ensure the account data is valid and has the correct length.
-    pub fn from_account_unchecked(account: &AccountView) -> Self {
-        unsafe { Self(account.borrow_unchecked().as_ptr()) }
+    /// The account data must not be mutably borrowed, through a [`VaultMut`]
+    /// or `borrow_unchecked_mut`, while the returned view is alive.
+    pub unsafe fn from_account_unchecked(account: &'a AccountView) -> Self {
+        // SAFETY: the caller rules out a live mutable borrow
+        unsafe { Self(account.borrow_unchecked()) }
     }
 
     /// Create a Vault from an AccountView, checking discriminator and length
-    pub fn from_account(account: &AccountView) -> Self {
-        assert_eq!(account.data_len(), Self::LEN, "Invalid vault data length");
+    ///
+    /// # Safety
+    /// Same as [`Vault::from_account_unchecked`].
+    pub unsafe fn from_account(account: &'a AccountView) -> Result<Self, ProgramError> {
+        // SAFETY: the caller rules out a live mutable borrow
+        Self::from_slice(unsafe { account.borrow_unchecked() })
+    }
+
+    /// Create a Vault over raw account data, checking discriminator and length
+    ///
+    /// Data longer than [`Vault::LEN`] is accepted and only the known prefix
+    /// is read, so a vault written by a later layout that appends fields
+    /// still parses.
+    pub fn from_slice(data: &'a [u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        let vault = Self(data);
+        if vault.discriminator() != VAULT_DISCRIMINATOR {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        Ok(vault)
+    }
+
+    /// Create a Vault view over a stack copy of the account data
+    ///
+    /// Alloc-free counterpart of [`Vault::to_bytes`] for clients that already
+    /// hold the raw account bytes.
+    pub fn from_bytes(bytes: &'a [u8; Vault::LEN]) -> Self {
+        Self(bytes)
+    }
 
-        let vault = Self::from_account_unchecked(account);
+    /// Copy the full vault state into a stack array, mirroring the raw layout
+    pub fn to_bytes(&self) -> [u8; Vault::LEN] {
+        let mut bytes = [0u8; Vault::LEN];
+        bytes.copy_from_slice(&self.0[..Self::LEN]);
+        bytes
+    }
 
-        assert_eq!(
-            vault.discriminator(),
-            VAULT_DISCRIMINATOR,
-            "Invalid vault discriminator"
-        );
+    /// Fixed-size field at `offset`
+    fn field<const N: usize>(&self, offset: usize) -> &'a [u8; N] {
+        let data: &'a [u8] = self.0;
+        data[offset..offset + N].try_into().unwrap()
+    }
 
-        vault
+    /// Address field at `offset`
+    fn address(&self, offset: usize) -> &'a Address {
+        let bytes: &'a [u8; 32] = self.field(offset);
+        // SAFETY: `Address` is a transparent wrapper over `[u8; 32]`
+        unsafe { &*(bytes as *const [u8; 32] as *const Address) }
     }
 
     /// Get the discriminator
     pub fn discriminator(&self) -> [u8; 8] {
-        unsafe { *(self.0.add(Self::DISCRIMINATOR_OFFSET) as *const [u8; 8]) }
+        *self.field(Self::DISCRIMINATOR_OFFSET)
     }
 
     /// Get the owner pubkey
-    pub fn owner(&self) -> &Address {
-        unsafe { &*(self.0.add(Self::OWNER_OFFSET) as *const Address) }
+    pub fn owner(&self) -> &'a Address {
+        self.address(Self::OWNER_OFFSET)
+    }
+
+    /// Get a copy of the owner pubkey bytes
+    ///
+    /// Unlike [`Vault::owner`], the result does not borrow the account data.
+    pub fn owner_bytes(&self) -> [u8; 32] {
+        *self.field(Self::OWNER_OFFSET)
+    }
+
+    /// Get the amount (`VaultAmount`)
+    pub fn amount(&self) -> VaultAmount {
+        VaultAmount::from_le_bytes(*self.field(Self::AMOUNT_OFFSET))
+    }
+
+    /// Get the pubkey of the most recent depositor
+    pub fn last_depositor(&self) -> &'a Address {
+        self.address(Self::LAST_DEPOSITOR_OFFSET)
+    }
+
+    /// Get the number of times the depositor changed between consecutive deposits (u32)
+    ///
+    /// This approximates the number of distinct depositors: it only counts
+    /// deposits whose depositor differs from the previous one.
+    pub fn depositor_changes(&self) -> u32 {
+        u32::from_le_bytes(*self.field(Self::DEPOSITOR_CHANGES_OFFSET))
+    }
+
+    /// Get the fee payer rebate paid by `WithdrawWithRebate` (u64)
+    pub fn rebate_lamports(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::REBATE_LAMPORTS_OFFSET))
+    }
+
+    /// Get the sequence number of the last mutating instruction (u64)
+    pub fn seq(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::SEQ_OFFSET))
+    }
+
+    /// Get the programs allowed to withdraw via CPI
+    ///
+    /// An empty list leaves CPI withdraws unrestricted.
+    pub fn allowed_callers(&self) -> &'a [Address] {
+        let count =
+            (self.0[Self::ALLOWED_CALLERS_COUNT_OFFSET] as usize).min(Self::MAX_ALLOWED_CALLERS);
+        let bytes: &'a [u8] = &self.0[Self::ALLOWED_CALLERS_OFFSET..Self::CREATED_SLOT_OFFSET];
+        // SAFETY: `bytes` holds `MAX_ALLOWED_CALLERS` 32-byte keys and
+        // `Address` is a transparent wrapper over `[u8; 32]`
+        unsafe { core::slice::from_raw_parts(bytes.as_ptr() as *const Address, count) }
+    }
+
+    /// Get the slot the vault was initialized in (u64)
+    pub fn created_slot(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::CREATED_SLOT_OFFSET))
+    }
+
+    /// Get the number of slots after creation before deposits are accepted
+    /// (u64, 0 disables the delay)
+    pub fn deposit_delay_slots(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::DEPOSIT_DELAY_SLOTS_OFFSET))
+    }
+
+    /// Get the key the vault PDA was derived from
+    ///
+    /// This is the original owner and does not change when ownership is
+    /// transferred, since the PDA address cannot move.
+    pub fn seed_owner(&self) -> &'a Address {
+        self.address(Self::SEED_OWNER_OFFSET)
+    }
+
+    /// Get the PDA bump recorded at initialize
+    pub fn bump(&self) -> u8 {
+        self.0[Self::BUMP_OFFSET]
+    }
+
+    /// Get the option bits set with `SetOptions`
+    pub fn options(&self) -> u8 {
+        self.0[Self::OPTIONS_OFFSET]
+    }
+
+    /// Get the `PROGRAM_VERSION` of the build that initialized the vault
+    pub fn created_by_version(&self) -> u32 {
+        u32::from_le_bytes(*self.field(Self::CREATED_BY_VERSION_OFFSET))
+    }
+
+    /// Get the label the vault PDA was derived with
+    pub fn label(&self) -> &'a [u8; VAULT_LABEL_LEN] {
+        self.field(Self::LABEL_OFFSET)
+    }
+
+    /// Get the slot before which withdrawals are rejected (u64, 0 for none)
+    pub fn unlock_slot(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::UNLOCK_SLOT_OFFSET))
+    }
+
+    /// Get the most the vault may track (u64, 0 for unlimited)
+    pub fn max_balance(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::MAX_BALANCE_OFFSET))
+    }
+
+    /// Whether deposits and withdrawals are frozen by `SetPaused`
+    pub fn is_paused(&self) -> bool {
+        self.0[Self::PAUSED_OFFSET] != 0
+    }
+
+    /// Get the rule set chosen at initialize
+    ///
+    /// Only `Initialize` writes the type, so an unknown value cannot occur
+    /// on a program-owned vault; it reads as `Standard`.
+    pub fn vault_type(&self) -> VaultType {
+        VaultType::try_from(self.0[Self::VAULT_TYPE_OFFSET]).unwrap_or(VaultType::Standard)
+    }
+
+    /// Get the escrow counterparty or custody delegate (all zero for other
+    /// types)
+    pub fn party(&self) -> &'a Address {
+        self.address(Self::PARTY_OFFSET)
+    }
+
+    /// Get the authority, which may pause the vault, change its settings and
+    /// transfer its ownership
+    pub fn authority(&self) -> &'a Address {
+        self.address(Self::AUTHORITY_OFFSET)
+    }
+
+    /// Get the number of deposits credited over the vault's lifetime (u64)
+    pub fn deposit_count(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::DEPOSIT_COUNT_OFFSET))
+    }
+
+    /// Get the number of withdrawals paid out over the vault's lifetime (u64)
+    pub fn withdraw_count(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::WITHDRAW_COUNT_OFFSET))
+    }
+
+    /// Get the smallest amount a single deposit may credit (u64, 0 for none)
+    pub fn min_deposit(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::MIN_DEPOSIT_OFFSET))
+    }
+
+    /// Get the share of each deposit sent to the treasury (u16 basis points,
+    /// 0 for none)
+    pub fn deposit_fee_bps(&self) -> u16 {
+        u16::from_le_bytes(*self.field(Self::DEPOSIT_FEE_BPS_OFFSET))
+    }
+
+    /// Get the largest withdrawal that needs no cosigner (u64, 0 for no
+    /// limit)
+    pub fn single_sig_limit(&self) -> u64 {
+        u64::from_le_bytes(*self.field(Self::SINGLE_SIG_LIMIT_OFFSET))
+    }
+
+    /// Get the cosigner that must also sign withdrawals above the single
+    /// signature limit
+    pub fn cosigner(&self) -> &'a Address {
+        self.address(Self::COSIGNER_OFFSET)
+    }
+}
+
+impl<'a> VaultMut<'a> {
+    /// Create a VaultMut from an AccountView reference
+    ///
+    /// # Safety
+    /// The caller must ensure no other view of the account data is used while
+    /// this one is alive.
+    pub unsafe fn from_account_unchecked(account: &'a AccountView) -> Self {
+        // SAFETY: the caller rules out any other live view
+        unsafe { Self(account.borrow_unchecked_mut()) }
+    }
+
+    /// Create a VaultMut from an AccountView, checking discriminator and length
+    ///
+    /// # Safety
+    /// Same as [`VaultMut::from_account_unchecked`].
+    pub unsafe fn from_account(account: &'a AccountView) -> Result<Self, ProgramError> {
+        // SAFETY: the read view is dropped before the mutable one is created
+        unsafe {
+            Vault::from_account(account)?;
+            Ok(Self::from_account_unchecked(account))
+        }
+    }
+
+    /// Create a VaultMut over a stack copy of the account data
+    pub fn from_bytes(bytes: &'a mut [u8; Vault::LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Reborrow as a read-only [`Vault`]
+    pub fn view(&self) -> Vault<'_> {
+        Vault(self.0)
+    }
+
+    /// Fixed-size field at `offset`
+    fn field_mut<const N: usize>(&mut self, offset: usize) -> &mut [u8; N] {
+        (&mut self.0[offset..offset + N]).try_into().unwrap()
+    }
+
+    /// Set the discriminator
+    pub fn set_discriminator(&mut self, discriminator: &[u8; 8]) {
+        *self.field_mut(Vault::DISCRIMINATOR_OFFSET) = *discriminator;
+    }
+
+    /// Set the discriminator, owner and amount at once via
+    /// [`VaultData::pack_into`]
+    pub fn set_header(&mut self, header: &VaultData) {
+        header.pack_into(self.0);
+    }
+
+    /// Zero the whole account and stamp it with `CLOSED_DISCRIMINATOR`
+    pub fn mark_closed(&mut self) {
+        self.0.fill(0);
+        self.set_discriminator(&CLOSED_DISCRIMINATOR);
+    }
+
+    /// Set the owner pubkey
+    pub fn set_owner(&mut self, owner: &Address) {
+        *self.field_mut(Vault::OWNER_OFFSET) = *owner.as_array();
+    }
+
+    /// Set the amount (`VaultAmount`)
+    pub fn set_amount(&mut self, amount: VaultAmount) {
+        *self.field_mut(Vault::AMOUNT_OFFSET) = amount.to_le_bytes();
+    }
+
+    /// Set the pubkey of the most recent depositor
+    pub fn set_last_depositor(&mut self, depositor: &Address) {
+        *self.field_mut(Vault::LAST_DEPOSITOR_OFFSET) = *depositor.as_array();
+    }
+
+    /// Set the depositor change count (u32)
+    pub fn set_depositor_changes(&mut self, changes: u32) {
+        *self.field_mut(Vault::DEPOSITOR_CHANGES_OFFSET) = changes.to_le_bytes();
+    }
+
+    /// Set the fee payer rebate (u64)
+    pub fn set_rebate_lamports(&mut self, lamports: u64) {
+        *self.field_mut(Vault::REBATE_LAMPORTS_OFFSET) = lamports.to_le_bytes();
+    }
+
+    /// Set the event sequence number (u64)
+    pub fn set_seq(&mut self, seq: u64) {
+        *self.field_mut(Vault::SEQ_OFFSET) = seq.to_le_bytes();
+    }
+
+    /// Replace the programs allowed to withdraw via CPI
+    ///
+    /// Unused slots are zeroed. At most [`Vault::MAX_ALLOWED_CALLERS`] keys
+    /// are stored; instruction parsing rejects longer lists.
+    pub fn set_allowed_callers(&mut self, callers: &[[u8; 32]]) {
+        let count = callers.len().min(Vault::MAX_ALLOWED_CALLERS);
+        self.0[Vault::ALLOWED_CALLERS_COUNT_OFFSET] = count as u8;
+        let slots = &mut self.0[Vault::ALLOWED_CALLERS_OFFSET..Vault::CREATED_SLOT_OFFSET];
+        slots.fill(0);
+        for (slot, caller) in slots.chunks_exact_mut(32).zip(&callers[..count]) {
+            slot.copy_from_slice(caller);
+        }
+    }
+
+    /// Set the creation slot (u64)
+    pub fn set_created_slot(&mut self, slot: u64) {
+        *self.field_mut(Vault::CREATED_SLOT_OFFSET) = slot.to_le_bytes();
+    }
+
+    /// Set the deposit delay (u64 slots, 0 disables the delay)
+    pub fn set_deposit_delay_slots(&mut self, slots: u64) {
+        *self.field_mut(Vault::DEPOSIT_DELAY_SLOTS_OFFSET) = slots.to_le_bytes();
+    }
+
+    /// Set the key the vault PDA was derived from
+    pub fn set_seed_owner(&mut self, seed_owner: &Address) {
+        *self.field_mut(Vault::SEED_OWNER_OFFSET) = *seed_owner.as_array();
+    }
+
+    /// Set the PDA bump
+    pub fn set_bump(&mut self, bump: u8) {
+        self.0[Vault::BUMP_OFFSET] = bump;
+    }
+
+    /// Set the option bits
+    pub fn set_options(&mut self, options: u8) {
+        self.0[Vault::OPTIONS_OFFSET] = options;
+    }
+
+    /// Set the program version that created the vault (u32)
+    pub fn set_created_by_version(&mut self, version: u32) {
+        *self.field_mut(Vault::CREATED_BY_VERSION_OFFSET) = version.to_le_bytes();
+    }
+
+    /// Set the label the vault PDA was derived with
+    pub fn set_label(&mut self, label: &[u8; VAULT_LABEL_LEN]) {
+        *self.field_mut(Vault::LABEL_OFFSET) = *label;
+    }
+
+    /// Set the unlock slot (u64)
+    pub fn set_unlock_slot(&mut self, slot: u64) {
+        *self.field_mut(Vault::UNLOCK_SLOT_OFFSET) = slot.to_le_bytes();
+    }
+
+    /// Set the maximum balance (u64, 0 for unlimited)
+    pub fn set_max_balance(&mut self, max_balance: u64) {
+        *self.field_mut(Vault::MAX_BALANCE_OFFSET) = max_balance.to_le_bytes();
+    }
+
+    /// Set the paused flag
+    pub fn set_paused(&mut self, paused: bool) {
+        self.0[Vault::PAUSED_OFFSET] = paused as u8;
+    }
+
+    /// Set the authority pubkey
+    pub fn set_authority(&mut self, authority: &Address) {
+        *self.field_mut(Vault::AUTHORITY_OFFSET) = *authority.as_array();
+    }
+
+    /// Set the lifetime deposit count (u64)
+    pub fn set_deposit_count(&mut self, count: u64) {
+        *self.field_mut(Vault::DEPOSIT_COUNT_OFFSET) = count.to_le_bytes();
+    }
+
+    /// Set the lifetime withdrawal count (u64)
+    pub fn set_withdraw_count(&mut self, count: u64) {
+        *self.field_mut(Vault::WITHDRAW_COUNT_OFFSET) = count.to_le_bytes();
+    }
+
+    /// Set the minimum deposit (u64, 0 for none)
+    pub fn set_min_deposit(&mut self, min_deposit: u64) {
+        *self.field_mut(Vault::MIN_DEPOSIT_OFFSET) = min_deposit.to_le_bytes();
+    }
+
+    /// Set the deposit fee (u16 basis points, 0 for none)
+    pub fn set_deposit_fee_bps(&mut self, fee_bps: u16) {
+        *self.field_mut(Vault::DEPOSIT_FEE_BPS_OFFSET) = fee_bps.to_le_bytes();
+    }
+
+    /// Set the single signature limit (u64, 0 for no limit) and the cosigner
+    /// required above it
+    pub fn set_cosigner(&mut self, single_sig_limit: u64, cosigner: &Address) {
+        *self.field_mut(Vault::SINGLE_SIG_LIMIT_OFFSET) = single_sig_limit.to_le_bytes();
+        *self.field_mut(Vault::COSIGNER_OFFSET) = *cosigner.as_array();
     }
 
-    /// Get the amount (u64)
-    pub fn amount(&self) -> u64 {
-        unsafe { u64::from_le_bytes(*(self.0.add(Self::AMOUNT_OFFSET) as *const [u8; 8])) }
+    /// Set the vault type and its party key
+    pub fn set_vault_type(&mut self, vault_type: VaultType, party: &Address) {
+        self.0[Vault::VAULT_TYPE_OFFSET] = vault_type as u8;
+        *self.field_mut(Vault::PARTY_OFFSET) = *party.as_array();
     }
 }
@@ -0,0 +1,103 @@
+use pinocchio::{cpi::Seed, error::ProgramError, AccountView, Address};
+
+use crate::error::VaultError;
+
+/// Vault counter account discriminator
+pub const VAULT_COUNTER_DISCRIMINATOR: [u8; 8] = *b"VltCount";
+
+/// Seed prefix of the per-owner vault counter PDA
+pub const VAULT_COUNTER_SEED: &[u8] = b"vault_counter";
+
+/// Vaults one seed owner may hold at a time, across all labels
+pub const MAX_VAULTS_PER_OWNER: u32 = 8;
+
+/// Signer seeds of the vault counter PDA: `[b"vault_counter", owner, bump]`
+pub fn vault_counter_signer_seeds<'a>(owner: &'a Address, bump: &'a [u8; 1]) -> [Seed<'a>; 3] {
+    [
+        Seed::from(VAULT_COUNTER_SEED),
+        Seed::from(owner.as_ref()),
+        Seed::from(bump),
+    ]
+}
+
+/// Derive the vault counter PDA of `owner` with a stored `bump`
+///
+/// Returns `None` if the seeds land on the curve.
+pub fn create_vault_counter_address(
+    owner: &Address,
+    bump: u8,
+    program_id: &Address,
+) -> Option<Address> {
+    Address::create_program_address(&[VAULT_COUNTER_SEED, owner.as_ref(), &[bump]], program_id).ok()
+}
+
+/// Find the canonical vault counter PDA and bump of `owner`
+pub fn find_vault_counter_address(owner: &Address, program_id: &Address) -> (Address, u8) {
+    Address::find_program_address(&[VAULT_COUNTER_SEED, owner.as_ref()], program_id)
+}
+
+/// Vault counter account layout, one per seed owner, counting the vaults
+/// derived from that owner that currently exist:
+/// - [0..8]   discriminator, `VAULT_COUNTER_DISCRIMINATOR` (8 bytes)
+/// - [8..12]  vault count (4 bytes, u32 LE)
+/// - [12]     PDA bump (1 byte)
+pub struct VaultCounter<'a>(&'a [u8]);
+
+impl<'a> VaultCounter<'a> {
+    pub const LEN: usize = Self::BUMP_OFFSET + 1; // 13 bytes
+
+    pub const DISCRIMINATOR_OFFSET: usize = 0;
+    pub const VAULT_COUNT_OFFSET: usize = 8;
+    pub const BUMP_OFFSET: usize = Self::VAULT_COUNT_OFFSET + 4;
+
+    /// Create a VaultCounter from an AccountView, checking discriminator and
+    /// length
+    ///
+    /// # Safety
+    /// The account data must not be mutably borrowed while the returned view
+    /// is alive.
+    pub unsafe fn from_account(account: &'a AccountView) -> Result<Self, ProgramError> {
+        if account.data_len() != Self::LEN {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        // SAFETY: the caller rules out a live mutable borrow
+        let counter = Self(unsafe { account.borrow_unchecked() });
+        if counter.discriminator() != VAULT_COUNTER_DISCRIMINATOR {
+            return Err(VaultError::InvalidDiscriminator.into());
+        }
+
+        Ok(counter)
+    }
+
+    /// Fixed-size field at `offset`
+    fn field<const N: usize>(&self, offset: usize) -> &'a [u8; N] {
+        let data: &'a [u8] = self.0;
+        data[offset..offset + N].try_into().unwrap()
+    }
+
+    /// Get the discriminator
+    pub fn discriminator(&self) -> [u8; 8] {
+        *self.field(Self::DISCRIMINATOR_OFFSET)
+    }
+
+    /// Get the number of the owner's vaults that exist (u32)
+    pub fn vault_count(&self) -> u32 {
+        u32::from_le_bytes(*self.field(Self::VAULT_COUNT_OFFSET))
+    }
+
+    /// Get the PDA bump
+    pub fn bump(&self) -> u8 {
+        self.0[Self::BUMP_OFFSET]
+    }
+
+    /// Write a vault counter into `data`, which must be exactly
+    /// [`VaultCounter::LEN`] bytes
+    pub fn write(data: &mut [u8], vault_count: u32, bump: u8) {
+        data[Self::DISCRIMINATOR_OFFSET..Self::VAULT_COUNT_OFFSET]
+            .copy_from_slice(&VAULT_COUNTER_DISCRIMINATOR);
+        data[Self::VAULT_COUNT_OFFSET..Self::BUMP_OFFSET]
+            .copy_from_slice(&vault_count.to_le_bytes());
+        data[Self::BUMP_OFFSET] = bump;
+    }
+}
@@ -0,0 +1,49 @@
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+use solana_program_log::log;
+
+use crate::state::vault::{VaultAmount, VaultMut};
+
+/// Increment the vault's event sequence number and log it
+///
+/// Every mutating instruction calls this exactly once on success, logging
+/// `VAULT_SEQ kind=<kind> seq=<u64>`. Sequence numbers start at 1 and have
+/// no gaps, so indexers can order events and detect missed ones.
+pub fn record(vault: &AccountView, kind: &str) -> ProgramResult {
+    // SAFETY: handlers never hold a view of the vault data across this call
+    let mut vault_state = unsafe { VaultMut::from_account_unchecked(vault) };
+    let seq = vault_state
+        .view()
+        .seq()
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    vault_state.set_seq(seq);
+
+    log!("VAULT_SEQ kind={} seq={}", kind, seq);
+
+    Ok(())
+}
+
+/// Log a balance change as `<tag> owner=<hex> amount=<u64> balance=<amount>`
+///
+/// `owner` is printed as 64 lowercase hex characters, which is far cheaper
+/// to encode on-chain than base58. `balance` is the vault's tracked amount
+/// after the change.
+pub fn log_balance_change(tag: &str, owner: &Address, amount: u64, balance: VaultAmount) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    let mut hex = [0u8; 64];
+    for (pair, byte) in hex.chunks_exact_mut(2).zip(owner.as_ref()) {
+        pair[0] = HEX[(byte >> 4) as usize];
+        pair[1] = HEX[(byte & 0x0f) as usize];
+    }
+    // SAFETY: every byte is an ASCII hex digit
+    let hex = unsafe { core::str::from_utf8_unchecked(&hex) };
+
+    log!(
+        "{} owner={} amount={} balance={}",
+        tag,
+        hex,
+        amount,
+        balance
+    );
+}
@@ -1,24 +1,78 @@
 use pinocchio::error::ProgramError;
-use pinocchio::{AccountView, Address};
+use pinocchio::sysvars::rent::Rent;
+use pinocchio::AccountView;
+
+use crate::error::VaultError;
 
 // =============================================================================
 // Basic Account Checks
 // =============================================================================
 
-/// Check if the account is a signer
-pub fn signer_check(account: &AccountView) -> Result<(), ProgramError> {
-    if !account.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
+/// Check that `account` can act as a vault owner
+///
+/// Programs cannot sign as ordinary accounts, so a transaction that puts the
+/// system program (or any executable) in the owner slot would otherwise fail
+/// the signer check with a misleading error.
+pub fn owner_account_check(account: &AccountView) -> Result<(), ProgramError> {
+    if account.executable() || account.address() == &pinocchio_system::ID {
+        return Err(VaultError::InvalidOwnerAccount.into());
     }
 
     Ok(())
 }
 
-/// Check if the account is owned by the given program
-pub fn owner_check(account: &AccountView, owner: &Address) -> Result<(), ProgramError> {
-    if !account.owned_by(owner) {
-        return Err(ProgramError::IllegalOwner);
+/// Check that `account` is the system program
+///
+/// The transfer and create CPIs would fail on any other account anyway, but
+/// with an error that does not point at the bad account.
+pub fn system_program_check(account: &AccountView) -> Result<(), ProgramError> {
+    if account.address() != &pinocchio_system::ID {
+        return Err(ProgramError::IncorrectProgramId);
     }
 
     Ok(())
 }
+
+/// Check that `account` is still uninitialized: system-owned with no data
+///
+/// Lamports are allowed, so a PDA pre-funded by a third party can still be
+/// created. Anything else would make the create CPI fail with an error that
+/// does not say the account is already in use.
+pub fn uninitialized_check(account: &AccountView) -> Result<(), ProgramError> {
+    if !account.owned_by(&pinocchio_system::ID) || !account.is_data_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    Ok(())
+}
+
+// =============================================================================
+// Invocation Context
+// =============================================================================
+
+/// Current invocation depth: 1 for a top-level instruction, 2 or more when
+/// running inside a cross-program invocation
+pub fn stack_height() -> u64 {
+    #[cfg(target_os = "solana")]
+    unsafe {
+        pinocchio::syscalls::sol_get_stack_height()
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    1
+}
+
+// =============================================================================
+// Rent
+// =============================================================================
+
+/// Lamports held by `vault` beyond its rent-exempt minimum
+///
+/// Takes an already fetched `Rent`: handlers read the sysvar once with
+/// `Rent::get()` and pass it to every rent-dependent check. The minimum is
+/// for the account's actual size, which may exceed `Vault::LEN`.
+pub fn vault_backing(vault: &AccountView, rent: &Rent) -> Result<u64, ProgramError> {
+    Ok(vault
+        .lamports()
+        .saturating_sub(rent.try_minimum_balance(vault.data_len())?))
+}
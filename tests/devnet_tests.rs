@@ -8,19 +8,25 @@
 //!
 //! Run with: cargo test --test devnet_tests -- --nocapture
 //!
+//! Without a keypair at ~/.config/solana/id.json the tests print a notice
+//! and pass without touching devnet.
+//!
 //! ⚠️ Each test run costs real devnet SOL for transaction fees + rent.
 
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::thread::sleep;
 use std::time::Duration;
 
-use solana_instruction::{AccountMeta, Instruction};
+use solana_instruction::Instruction;
 use solana_keypair::Keypair;
 use solana_message::Message;
 use solana_pubkey::Pubkey;
 use solana_rpc_client::rpc_client::RpcClient;
 use solana_signer::Signer;
 use solana_transaction::Transaction;
+use vault::client::{build_deposit_ix, build_initialize_ix, build_withdraw_ix};
+use vault::state::VAULT_DISCRIMINATOR;
 
 // ─── Constants ─────────────────────────────────────────────────────────
 
@@ -40,17 +46,49 @@ fn rpc() -> RpcClient {
     RpcClient::new(DEVNET_RPC.to_string())
 }
 
+/// Why the payer keypair could not be loaded
+#[derive(Debug)]
+enum LoadPayerError {
+    /// No keypair file, so devnet is not configured here
+    Missing(PathBuf),
+    /// A keypair file exists but cannot be used
+    Invalid(String),
+}
+
 /// Load the default Solana CLI keypair (~/.config/solana/id.json)
-fn load_payer() -> Keypair {
+fn load_payer() -> Result<Keypair, LoadPayerError> {
     let keypair_path = dirs::home_dir()
-        .expect("No home dir")
+        .ok_or_else(|| LoadPayerError::Invalid("No home dir".to_string()))?
         .join(".config/solana/id.json");
-    let keypair_bytes = std::fs::read_to_string(&keypair_path)
-        .unwrap_or_else(|_| panic!("Cannot read keypair at {:?}", keypair_path));
-    let bytes: Vec<u8> = serde_json::from_str(&keypair_bytes).expect("Invalid keypair JSON");
+    if !keypair_path.exists() {
+        return Err(LoadPayerError::Missing(keypair_path));
+    }
+    let keypair_bytes = std::fs::read_to_string(&keypair_path).map_err(|e| {
+        LoadPayerError::Invalid(format!("Cannot read keypair at {keypair_path:?}: {e}"))
+    })?;
+    let bytes: Vec<u8> = serde_json::from_str(&keypair_bytes)
+        .map_err(|e| LoadPayerError::Invalid(format!("Invalid keypair JSON: {e}")))?;
     // The JSON file has 64 bytes: first 32 are the secret key
-    let secret: [u8; 32] = bytes[..32].try_into().expect("Keypair too short");
-    Keypair::new_from_array(secret)
+    let secret: [u8; 32] = bytes
+        .get(..32)
+        .and_then(|secret| secret.try_into().ok())
+        .ok_or_else(|| LoadPayerError::Invalid("Keypair too short".to_string()))?;
+    Ok(Keypair::new_from_array(secret))
+}
+
+/// Load the payer, or print a notice and return `None` so the calling test
+/// can skip when no keypair is configured
+///
+/// A keypair file that exists but cannot be used still fails the test.
+fn payer_or_skip(test: &str) -> Option<Keypair> {
+    match load_payer() {
+        Ok(payer) => Some(payer),
+        Err(LoadPayerError::Missing(path)) => {
+            println!("Skipping {test}: no keypair at {path:?}");
+            None
+        }
+        Err(LoadPayerError::Invalid(reason)) => panic!("{reason}"),
+    }
 }
 
 fn vault_pda(owner: &Pubkey) -> (Pubkey, u8) {
@@ -74,53 +112,13 @@ fn wait() {
     sleep(Duration::from_secs(TX_DELAY));
 }
 
-// ─── Instruction Builders ──────────────────────────────────────────────
-
-fn build_initialize_ix(payer: &Pubkey, vault: &Pubkey, bump: u8) -> Instruction {
-    Instruction {
-        program_id: program_id(),
-        accounts: vec![
-            AccountMeta::new(*payer, true),
-            AccountMeta::new(*vault, false),
-            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
-        ],
-        data: vec![0x00, bump],
-    }
-}
-
-fn build_deposit_ix(owner: &Pubkey, vault: &Pubkey, amount: u64) -> Instruction {
-    let mut data = vec![0x01];
-    data.extend_from_slice(&amount.to_le_bytes());
-    Instruction {
-        program_id: program_id(),
-        accounts: vec![
-            AccountMeta::new(*owner, true),
-            AccountMeta::new(*vault, false),
-            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
-        ],
-        data,
-    }
-}
-
-fn build_withdraw_ix(owner: &Pubkey, vault: &Pubkey, amount: u64, bump: u8) -> Instruction {
-    let mut data = vec![0x02];
-    data.extend_from_slice(&amount.to_le_bytes());
-    data.push(bump);
-    Instruction {
-        program_id: program_id(),
-        accounts: vec![
-            AccountMeta::new(*owner, true),
-            AccountMeta::new(*vault, false),
-            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
-        ],
-        data,
-    }
-}
-
 /// Read the vault account and return (discriminator, owner, amount)
+///
+/// Only the known prefix is read, so accounts extended by a newer layout
+/// still parse.
 fn read_vault(client: &RpcClient, vault: &Pubkey) -> Option<([u8; 8], Pubkey, u64)> {
     let account = client.get_account(vault).ok()?;
-    if account.data.len() != 48 {
+    if account.data.len() < VAULT_LEN {
         return None;
     }
     let mut disc = [0u8; 8];
@@ -130,7 +128,7 @@ fn read_vault(client: &RpcClient, vault: &Pubkey) -> Option<([u8; 8], Pubkey, u6
     Some((disc, owner, amount))
 }
 
-const VAULT_DISCRIMINATOR: [u8; 8] = [0x56, 0x61, 0x75, 0x6c, 0x74, 0x21, 0x21, 0x21];
+const VAULT_LEN: usize = 447;
 
 // ─── Tests ─────────────────────────────────────────────────────────────
 //
@@ -143,8 +141,10 @@ const VAULT_DISCRIMINATOR: [u8; 8] = [0x56, 0x61, 0x75, 0x6c, 0x74, 0x21, 0x21,
 /// Full lifecycle: initialize → deposit → withdraw (all sequential)
 #[test]
 fn devnet_full_lifecycle() {
+    let Some(payer) = payer_or_skip("devnet_full_lifecycle") else {
+        return;
+    };
     let client = rpc();
-    let payer = load_payer();
     let (vault_pda, bump) = vault_pda(&payer.pubkey());
 
     println!("══════════════════════════════════════════════");
@@ -166,7 +166,7 @@ fn devnet_full_lifecycle() {
             );
         }
     } else {
-        let ix = build_initialize_ix(&payer.pubkey(), &vault_pda, bump);
+        let ix = build_initialize_ix(&program_id(), &payer.pubkey(), &vault_pda, bump);
         let sig = send_tx(&client, ix, &payer).expect("❌ Initialize failed");
         println!("  ✅ Initialize TX: {}", sig);
         wait();
@@ -197,7 +197,7 @@ fn devnet_full_lifecycle() {
         deposit_amount as f64 / 1e9
     );
 
-    let ix = build_deposit_ix(&payer.pubkey(), &vault_pda, deposit_amount);
+    let ix = build_deposit_ix(&program_id(), &payer.pubkey(), &vault_pda, deposit_amount);
     let sig = send_tx(&client, ix, &payer).expect("❌ Deposit failed");
     println!("  ✅ Deposit TX: {}", sig);
     wait();
@@ -233,7 +233,13 @@ fn devnet_full_lifecycle() {
 
     let payer_balance_before = client.get_balance(&payer.pubkey()).unwrap();
 
-    let ix = build_withdraw_ix(&payer.pubkey(), &vault_pda, withdraw_amount, bump);
+    let ix = build_withdraw_ix(
+        &program_id(),
+        &payer.pubkey(),
+        &vault_pda,
+        withdraw_amount,
+        bump,
+    );
     let sig = send_tx(&client, ix, &payer).expect("❌ Withdraw failed");
     println!("  ✅ Withdraw TX: {}", sig);
     wait();
@@ -273,8 +279,10 @@ fn devnet_full_lifecycle() {
 /// Read-only: just check current vault state (safe to run anytime)
 #[test]
 fn devnet_check_vault_state() {
+    let Some(payer) = payer_or_skip("devnet_check_vault_state") else {
+        return;
+    };
     let client = rpc();
-    let payer = load_payer();
     let (vault_pda, _) = vault_pda(&payer.pubkey());
 
     println!("\n=== DEVNET: Check Vault State ===");